@@ -2,7 +2,7 @@ use std::env;
 use std::path::PathBuf;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let proto_file = "./proto/storage_api.proto";
+    let proto_files = ["./proto/storage_api.proto", "./proto/storage_api_v2.proto"];
     let out_dir = PathBuf::from(env::var("OUT_DIR").unwrap());
 
     tonic_build::configure()
@@ -11,7 +11,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         .build_server(true)
         .file_descriptor_set_path(out_dir.join("database_descriptor.bin"))
         .out_dir("./src")
-        .compile(&[proto_file], &["proto"])?;
+        .compile(&proto_files, &["proto"])?;
 
     Ok(())
 }
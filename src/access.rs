@@ -0,0 +1,510 @@
+use serde::Deserialize;
+use std::fmt;
+use tonic::transport::server::UdsConnectInfo;
+use tonic::{Request, Status};
+
+use crate::config::{AccessConfig, TokenConfig};
+use crate::tls::{has_peer_certificate, peer_common_name};
+
+/// Namespace in an access rule that matches every namespace.
+pub const ALL_NAMESPACES: &str = "*";
+
+/// Rights on a namespace. Every right includes the rights listed before it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Permission {
+    /// Read, Search, ListNodes, NamespaceInfo and Export; ListNamespaces
+    /// requires the right on all namespaces (`"*"`).
+    Read,
+    /// Write, Delete, DeleteNodes and Import.
+    Write,
+    /// DropNamespace and DestroyDB; DestroyDB requires the right on all
+    /// namespaces (`"*"`).
+    Admin,
+}
+
+/// Calls of the Database services, named as in the API. The calls of both API
+/// versions share a name where they do the same.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum Operation {
+    #[serde(rename = "DestroyDB")]
+    DestroyDb,
+    Write,
+    Read,
+    Delete,
+    Search,
+    DeleteNodes,
+    ListNodes,
+    WriteValue,
+    ReadValue,
+    CompareAndSwap,
+    WriteBatch,
+    ListNamespaces,
+    NamespaceInfo,
+    DropNamespace,
+    Export,
+    Import,
+    Watch,
+}
+
+impl Operation {
+    /// Returns the right the operation requires on its namespace.
+    pub fn permission(self) -> Permission {
+        match self {
+            Operation::Read
+            | Operation::ReadValue
+            | Operation::Search
+            | Operation::ListNodes
+            | Operation::ListNamespaces
+            | Operation::NamespaceInfo
+            | Operation::Export
+            | Operation::Watch => Permission::Read,
+            Operation::Write
+            | Operation::WriteValue
+            | Operation::Delete
+            | Operation::DeleteNodes
+            | Operation::CompareAndSwap
+            | Operation::WriteBatch
+            | Operation::Import => Permission::Write,
+            Operation::DestroyDb | Operation::DropNamespace => Permission::Admin,
+        }
+    }
+}
+
+impl fmt::Display for Operation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Operation::DestroyDb => write!(f, "DestroyDB"),
+            operation => write!(f, "{operation:?}"),
+        }
+    }
+}
+
+/// Identity of the client that sent a request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Identity {
+    /// Local process connected through the Unix domain socket.
+    Unix {
+        uid: u32,
+        gid: u32,
+        pid: Option<i32>,
+    },
+    /// Connection through the Unix domain socket whose peer credentials could not be read.
+    UnixUnknownPeer,
+    /// Client authenticated by a TLS client certificate.
+    Certificate { common_name: String },
+    /// Client authenticated by a TLS client certificate without a common name,
+    /// which no access rule can match.
+    UnknownCertificate,
+    /// Client authenticated by a bearer token (see `auth::TokenInterceptor`).
+    Token(TokenConfig),
+    /// Client connected through TCP without token authentication.
+    Network,
+}
+
+impl Identity {
+    /// Determines the identity of the client from the connection of `request`.
+    pub fn of<T>(request: &Request<T>) -> Identity {
+        match request.extensions().get::<UdsConnectInfo>() {
+            Some(info) => match &info.peer_cred {
+                Some(cred) => Identity::Unix {
+                    uid: cred.uid(),
+                    gid: cred.gid(),
+                    pid: cred.pid(),
+                },
+                None => Identity::UnixUnknownPeer,
+            },
+            // A token is only attached if the certificate, if any, is not
+            // enough to identify the client (see `auth::TokenInterceptor`).
+            None => match request.extensions().get::<TokenConfig>() {
+                Some(token) => Identity::Token(token.clone()),
+                None if has_peer_certificate(request) => match peer_common_name(request) {
+                    Some(common_name) => Identity::Certificate { common_name },
+                    None => Identity::UnknownCertificate,
+                },
+                None => Identity::Network,
+            },
+        }
+    }
+}
+
+impl fmt::Display for Identity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Identity::Unix { uid, gid, pid } => match pid {
+                Some(pid) => write!(f, "uid {uid} (gid {gid}, pid {pid})"),
+                None => write!(f, "uid {uid} (gid {gid})"),
+            },
+            Identity::UnixUnknownPeer => write!(f, "unidentified Unix socket peer"),
+            Identity::Certificate { common_name } => write!(f, "certificate '{common_name}'"),
+            Identity::UnknownCertificate => write!(f, "certificate without common name"),
+            Identity::Token(token) => write!(f, "token '{}'", token.name),
+            Identity::Network => write!(f, "network client"),
+        }
+    }
+}
+
+/// Checks whether `identity` may perform `operation` on `namespace`.
+///
+/// `namespace` is `None` for operations on the entire database, which are only
+/// granted by rules or tokens for all namespaces. The rules in `config` apply to
+/// Unix socket clients and TLS client certificates and grant the permission the
+/// operation requires. Tokens restrict other TCP clients in the same way and,
+/// if they list `operations`, to those. TCP clients with neither are denied while the rules are enabled,
+/// since any local process could connect that way.
+#[allow(clippy::result_large_err)]
+pub fn authorize(
+    config: &AccessConfig,
+    identity: &Identity,
+    namespace: Option<&str>,
+    operation: Operation,
+) -> Result<(), Status> {
+    let permission = operation.permission();
+    let granted = match identity {
+        Identity::Unix { .. }
+        | Identity::UnixUnknownPeer
+        | Identity::Certificate { .. }
+        | Identity::UnknownCertificate
+        | Identity::Network
+            if !config.enabled =>
+        {
+            return Ok(())
+        }
+        Identity::Unix { uid, gid, .. } => config
+            .rules
+            .iter()
+            .filter(|rule| rule.common_name.is_none())
+            .filter(|rule| rule.uid.is_none_or(|rule_uid| rule_uid == *uid))
+            .filter(|rule| rule.gid.is_none_or(|rule_gid| rule_gid == *gid))
+            .filter(|rule| covers(&rule.namespaces, namespace))
+            .map(|rule| rule.permission)
+            .max(),
+        Identity::UnixUnknownPeer | Identity::UnknownCertificate | Identity::Network => None,
+        Identity::Certificate { common_name } => config
+            .rules
+            .iter()
+            .filter(|rule| rule.common_name.as_ref() == Some(common_name))
+            .filter(|rule| covers(&rule.namespaces, namespace))
+            .map(|rule| rule.permission)
+            .max(),
+        Identity::Token(token) => (covers(&token.namespaces, namespace)
+            && (token.operations.is_empty() || token.operations.contains(&operation)))
+        .then_some(token.permission),
+    };
+    if granted.is_some_and(|granted| granted >= permission) {
+        return Ok(());
+    }
+
+    let target = match namespace {
+        Some(namespace) => format!("namespace '{namespace}'"),
+        None => String::from("the entire database"),
+    };
+    log::warn!("Denied {operation} on {target} to {identity}");
+    Err(Status::permission_denied(format!(
+        "{identity} may not {operation} on {target}, which needs {} permission",
+        format!("{permission:?}").to_lowercase()
+    )))
+}
+
+/// Whether a rule or token for `namespaces` applies to `namespace`.
+fn covers(namespaces: &[String], namespace: Option<&str>) -> bool {
+    namespaces
+        .iter()
+        .any(|candidate| candidate == ALL_NAMESPACES || Some(candidate.as_str()) == namespace)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{AccessRule, Config, UnixSocketConfig};
+    use crate::listener::bind_unix_socket;
+    use crate::memory::MemoryFacade;
+    use crate::server::DatabaseManager;
+    use crate::storage_api::database_client::DatabaseClient;
+    use crate::storage_api::database_server::DatabaseServer;
+    use crate::storage_api::{DestroyArguments, Key, KeyValue};
+    use std::os::unix::fs::MetadataExt;
+    use tokio::net::UnixStream;
+    use tonic::transport::{Endpoint, Server, Uri};
+
+    fn rule(
+        uid: Option<u32>,
+        gid: Option<u32>,
+        namespaces: &[&str],
+        permission: Permission,
+    ) -> AccessRule {
+        AccessRule {
+            uid,
+            gid,
+            common_name: None,
+            namespaces: namespaces
+                .iter()
+                .map(|namespace| namespace.to_string())
+                .collect(),
+            permission,
+        }
+    }
+
+    fn unix_identity(uid: u32, gid: u32) -> Identity {
+        Identity::Unix {
+            uid,
+            gid,
+            pid: None,
+        }
+    }
+
+    #[test]
+    fn authorize_disabled_test() {
+        let config = AccessConfig::default();
+
+        let result = authorize(
+            &config,
+            &unix_identity(1000, 1000),
+            Some("AppName"),
+            Operation::DropNamespace,
+        );
+
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn authorize_by_uid_test() {
+        let config = AccessConfig {
+            enabled: true,
+            rules: vec![
+                rule(Some(1000), None, &["AppName"], Permission::Write),
+                rule(None, None, &["Shared"], Permission::Read),
+            ],
+        };
+        let app = unix_identity(1000, 1000);
+        let other = unix_identity(1001, 1000);
+
+        assert!(authorize(&config, &app, Some("AppName"), Operation::Read).is_ok());
+        assert!(authorize(&config, &app, Some("AppName"), Operation::Write).is_ok());
+        assert!(authorize(&config, &app, Some("AppName"), Operation::DropNamespace).is_err());
+        assert!(authorize(&config, &app, Some("Shared"), Operation::Read).is_ok());
+        assert!(authorize(&config, &app, Some("Shared"), Operation::Write).is_err());
+        assert!(authorize(&config, &other, Some("AppName"), Operation::Read).is_err());
+        assert!(authorize(&config, &other, Some("Shared"), Operation::Read).is_ok());
+    }
+
+    #[test]
+    fn authorize_by_gid_and_all_namespaces_test() {
+        let config = AccessConfig {
+            enabled: true,
+            rules: vec![rule(None, Some(50), &[ALL_NAMESPACES], Permission::Admin)],
+        };
+
+        let result_admin = authorize(
+            &config,
+            &unix_identity(1000, 50),
+            None,
+            Operation::DestroyDb,
+        );
+        let result_other = authorize(
+            &config,
+            &unix_identity(1000, 1000),
+            Some(""),
+            Operation::Read,
+        );
+        let result_unknown = authorize(
+            &config,
+            &Identity::UnixUnknownPeer,
+            Some(""),
+            Operation::Read,
+        );
+
+        assert!(result_admin.is_ok());
+        assert_eq!(
+            result_other.unwrap_err().code(),
+            tonic::Code::PermissionDenied
+        );
+        assert_eq!(
+            result_unknown.unwrap_err().code(),
+            tonic::Code::PermissionDenied
+        );
+    }
+
+    #[test]
+    fn authorize_certificate_test() {
+        let mut certificate_rule = rule(None, None, &["Radio"], Permission::Write);
+        certificate_rule.common_name = Some("radio".to_string());
+        let config = AccessConfig {
+            enabled: true,
+            rules: vec![
+                certificate_rule,
+                rule(None, None, &["*"], Permission::Admin),
+            ],
+        };
+        let radio = Identity::Certificate {
+            common_name: "radio".to_string(),
+        };
+        let other = Identity::Certificate {
+            common_name: "navigation".to_string(),
+        };
+
+        assert!(authorize(&config, &radio, Some("Radio"), Operation::Write).is_ok());
+        assert!(authorize(&config, &radio, Some("Navigation"), Operation::Read).is_err());
+        assert!(authorize(&config, &other, Some("Radio"), Operation::Read).is_err());
+        assert!(authorize(
+            &config,
+            &Identity::UnknownCertificate,
+            Some("Radio"),
+            Operation::Read
+        )
+        .is_err());
+        assert!(authorize(
+            &config,
+            &unix_identity(1000, 1000),
+            None,
+            Operation::DestroyDb
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn authorize_token_test() {
+        let config = AccessConfig::default();
+        let token = |permission, operations| {
+            Identity::Token(TokenConfig {
+                name: "diagnostics".to_string(),
+                token: "secret".to_string(),
+                namespaces: vec!["Radio".to_string()],
+                permission,
+                operations,
+            })
+        };
+        let writer = token(Permission::Write, Vec::new());
+        let reader = token(
+            Permission::Admin,
+            vec![Operation::Read, Operation::DropNamespace],
+        );
+
+        assert!(authorize(&config, &writer, Some("Radio"), Operation::WriteBatch).is_ok());
+        assert!(authorize(&config, &writer, Some("Radio"), Operation::DropNamespace).is_err());
+        assert!(authorize(&config, &writer, Some("Navigation"), Operation::Read).is_err());
+        assert!(authorize(&config, &reader, Some("Radio"), Operation::Read).is_ok());
+        assert!(authorize(&config, &reader, Some("Radio"), Operation::DropNamespace).is_ok());
+        assert!(authorize(&config, &reader, Some("Radio"), Operation::ReadValue).is_err());
+        assert!(authorize(&config, &reader, Some("Radio"), Operation::Write).is_err());
+    }
+
+    #[test]
+    fn authorize_network_test() {
+        let config = AccessConfig {
+            enabled: true,
+            rules: vec![rule(None, None, &["*"], Permission::Admin)],
+        };
+
+        let result_enabled = authorize(&config, &Identity::Network, Some("Radio"), Operation::Read);
+        let result_disabled = authorize(
+            &AccessConfig::default(),
+            &Identity::Network,
+            None,
+            Operation::DestroyDb,
+        );
+
+        assert!(result_enabled.is_err());
+        assert!(result_disabled.is_ok());
+    }
+
+    #[test]
+    fn authorize_entire_database_needs_all_namespaces_test() {
+        let config = AccessConfig {
+            enabled: true,
+            rules: vec![rule(Some(1000), None, &["AppName"], Permission::Admin)],
+        };
+
+        let result = authorize(
+            &config,
+            &unix_identity(1000, 1000),
+            None,
+            Operation::DestroyDb,
+        );
+
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_peer_credentials_on_unix_socket() {
+        // Arrange
+        let socket = UnixSocketConfig {
+            path: std::env::temp_dir().join(format!("agl-storage-{}.sock", uuid::Uuid::new_v4())),
+            mode: 0o600,
+        };
+        let incoming = bind_unix_socket(&socket).unwrap();
+        // The socket file is owned by the uid of this process, which is also the peer uid.
+        let uid = std::fs::metadata(&socket.path).unwrap().uid();
+        let config = Config {
+            access: AccessConfig {
+                enabled: true,
+                rules: vec![
+                    rule(Some(uid), None, &["AppName"], Permission::Write),
+                    rule(Some(uid), None, &["Public"], Permission::Read),
+                ],
+            },
+            ..Config::default()
+        };
+        let database_manager = DatabaseManager::new(Box::new(MemoryFacade::new()), &config);
+        let server = Server::builder().add_service(DatabaseServer::new(database_manager));
+        let server_task = tokio::spawn(server.serve_with_incoming(incoming));
+
+        let socket_path = socket.path.clone();
+        let channel = Endpoint::from_static("http://localhost")
+            .connect_with_connector(tower::service_fn(move |_: Uri| {
+                UnixStream::connect(socket_path.clone())
+            }))
+            .await
+            .unwrap();
+        let mut client = DatabaseClient::new(channel);
+
+        // Act
+        let response_write_allowed = client
+            .write(KeyValue {
+                key: "Private.Info".to_string(),
+                value: "test".to_string(),
+                namespace: "AppName".to_string(),
+            })
+            .await;
+        let response_write_denied = client
+            .write(KeyValue {
+                key: "Private.Info".to_string(),
+                value: "test".to_string(),
+                namespace: "Public".to_string(),
+            })
+            .await;
+        let response_read_allowed = client
+            .read(Key {
+                key: "Private.Info".to_string(),
+                namespace: "Public".to_string(),
+            })
+            .await;
+        let response_read_denied = client
+            .read(Key {
+                key: "Private.Info".to_string(),
+                namespace: "OtherApp".to_string(),
+            })
+            .await;
+        let response_destroy = client.destroy_db(DestroyArguments {}).await;
+
+        // Assert
+        assert!(response_write_allowed.unwrap().into_inner().success);
+        assert_eq!(
+            response_write_denied.unwrap_err().code(),
+            tonic::Code::PermissionDenied
+        );
+        assert!(response_read_allowed.is_ok());
+        assert_eq!(
+            response_read_denied.unwrap_err().code(),
+            tonic::Code::PermissionDenied
+        );
+        assert_eq!(
+            response_destroy.unwrap_err().code(),
+            tonic::Code::PermissionDenied
+        );
+
+        // Clean up.
+        server_task.abort();
+        std::fs::remove_file(&socket.path).unwrap();
+    }
+}
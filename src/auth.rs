@@ -0,0 +1,224 @@
+use std::sync::Arc;
+use tonic::service::Interceptor;
+use tonic::transport::server::UdsConnectInfo;
+use tonic::{Request, Status};
+
+use crate::config::{AccessConfig, AuthConfig, TokenConfig};
+use crate::tls::peer_common_name;
+
+/// Interceptor that authenticates TCP clients by the bearer token in their
+/// `authorization` metadata.
+///
+/// The configuration of the accepted token is attached to the request, where
+/// the handlers check it against the namespace of the request (see
+/// `access::authorize`). Requests on the Unix domain socket are passed
+/// through, since those clients are identified by their peer credentials
+/// instead. So are requests with a TLS client certificate, but only if it has
+/// a common name and the access rules, which apply to it, are enabled.
+#[derive(Debug, Clone)]
+pub struct TokenInterceptor {
+    config: Arc<AuthConfig>,
+    /// Whether the access rules for certificates are enforced.
+    certificate_rules: bool,
+}
+
+impl TokenInterceptor {
+    pub fn new(config: &AuthConfig, access: &AccessConfig) -> TokenInterceptor {
+        TokenInterceptor {
+            config: Arc::new(config.clone()),
+            certificate_rules: access.enabled,
+        }
+    }
+
+    fn find_token(&self, presented: &str) -> Option<&TokenConfig> {
+        self.config
+            .tokens
+            .iter()
+            .find(|token| constant_time_eq(token.token.as_bytes(), presented.as_bytes()))
+    }
+}
+
+impl Interceptor for TokenInterceptor {
+    fn call(&mut self, mut request: Request<()>) -> Result<Request<()>, Status> {
+        if !self.config.enabled
+            || request.extensions().get::<UdsConnectInfo>().is_some()
+            || (self.certificate_rules && peer_common_name(&request).is_some())
+        {
+            return Ok(request);
+        }
+        let presented = request
+            .metadata()
+            .get("authorization")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .ok_or_else(|| Status::unauthenticated("Missing bearer token"))?;
+        let token = match self.find_token(presented.trim()) {
+            Some(token) => token.clone(),
+            None => {
+                log::warn!("Rejected request with an unknown bearer token");
+                return Err(Status::unauthenticated("Invalid bearer token"));
+            }
+        };
+        request.extensions_mut().insert(token);
+        Ok(request)
+    }
+}
+
+/// Compares two byte strings in time that depends only on their lengths.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::access::Permission;
+    use crate::config::Config;
+    use crate::memory::MemoryFacade;
+    use crate::server::DatabaseManager;
+    use crate::storage_api::database_client::DatabaseClient;
+    use crate::storage_api::database_server::DatabaseServer;
+    use crate::storage_api::{DestroyArguments, Key, KeyValue};
+    use tokio::net::TcpListener;
+    use tokio_stream::wrappers::TcpListenerStream;
+    use tonic::transport::{Channel, Server};
+
+    fn token(name: &str, namespaces: &[&str], permission: Permission) -> TokenConfig {
+        TokenConfig {
+            name: name.to_string(),
+            token: format!("{name}-secret"),
+            namespaces: namespaces
+                .iter()
+                .map(|namespace| namespace.to_string())
+                .collect(),
+            permission,
+            operations: Vec::new(),
+        }
+    }
+
+    fn with_token<T>(message: T, token: &str) -> Request<T> {
+        let mut request = Request::new(message);
+        request
+            .metadata_mut()
+            .insert("authorization", format!("Bearer {token}").parse().unwrap());
+        request
+    }
+
+    #[test]
+    fn interceptor_disabled_test() {
+        let mut interceptor =
+            TokenInterceptor::new(&AuthConfig::default(), &AccessConfig::default());
+
+        let result = interceptor.call(Request::new(()));
+
+        assert!(result.unwrap().extensions().get::<TokenConfig>().is_none());
+    }
+
+    #[test]
+    fn interceptor_attaches_token_test() {
+        let mut interceptor = TokenInterceptor::new(
+            &AuthConfig {
+                enabled: true,
+                tokens: vec![
+                    token("radio", &["Radio"], Permission::Write),
+                    token("admin", &["*"], Permission::Admin),
+                ],
+            },
+            &AccessConfig::default(),
+        );
+
+        let result_radio = interceptor.call(with_token((), "radio-secret"));
+        let result_missing = interceptor.call(Request::new(()));
+        let result_unknown = interceptor.call(with_token((), "radio-secre"));
+
+        let request = result_radio.unwrap();
+        assert_eq!(
+            request.extensions().get::<TokenConfig>().unwrap().name,
+            "radio"
+        );
+        assert_eq!(
+            result_missing.unwrap_err().code(),
+            tonic::Code::Unauthenticated
+        );
+        assert_eq!(
+            result_unknown.unwrap_err().code(),
+            tonic::Code::Unauthenticated
+        );
+    }
+
+    #[tokio::test]
+    async fn test_bearer_token_on_tcp() {
+        // Arrange
+        let config = Config {
+            auth: AuthConfig {
+                enabled: true,
+                tokens: vec![
+                    token("radio", &["Radio"], Permission::Write),
+                    token("admin", &["*"], Permission::Admin),
+                ],
+            },
+            ..Config::default()
+        };
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let database_manager = DatabaseManager::new(Box::new(MemoryFacade::new()), &config);
+        let server = Server::builder().add_service(DatabaseServer::with_interceptor(
+            database_manager,
+            TokenInterceptor::new(&config.auth, &config.access),
+        ));
+        let server_task =
+            tokio::spawn(server.serve_with_incoming(TcpListenerStream::new(listener)));
+
+        let channel = Channel::from_shared(format!("http://{address}"))
+            .unwrap()
+            .connect()
+            .await
+            .unwrap();
+        let mut client = DatabaseClient::new(channel);
+        let key_value = |namespace: &str| KeyValue {
+            key: "Radio.Volume".to_string(),
+            value: "12".to_string(),
+            namespace: namespace.to_string(),
+        };
+
+        // Act
+        let response_no_token = client.write(key_value("Radio")).await;
+        let response_write = client
+            .write(with_token(key_value("Radio"), "radio-secret"))
+            .await;
+        let response_other_namespace = client
+            .read(with_token(
+                Key {
+                    key: "Radio.Volume".to_string(),
+                    namespace: "Navigation".to_string(),
+                },
+                "radio-secret",
+            ))
+            .await;
+        let response_destroy_denied = client
+            .destroy_db(with_token(DestroyArguments {}, "radio-secret"))
+            .await;
+        let response_destroy = client
+            .destroy_db(with_token(DestroyArguments {}, "admin-secret"))
+            .await;
+
+        // Assert
+        assert_eq!(
+            response_no_token.unwrap_err().code(),
+            tonic::Code::Unauthenticated
+        );
+        assert!(response_write.unwrap().into_inner().success);
+        assert_eq!(
+            response_other_namespace.unwrap_err().code(),
+            tonic::Code::PermissionDenied
+        );
+        assert_eq!(
+            response_destroy_denied.unwrap_err().code(),
+            tonic::Code::PermissionDenied
+        );
+        assert!(response_destroy.unwrap().into_inner().success);
+
+        // Clean up.
+        server_task.abort();
+    }
+}
@@ -0,0 +1,95 @@
+use serde::Deserialize;
+
+use crate::config::RocksDbConfig;
+use crate::facade::{FacadeError, RocksDbFacade};
+use crate::memory::MemoryFacade;
+
+/// A change applied as part of a batch, see `StorageBackend::write_batch`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BatchOperation {
+    /// Writes `value` under `key`.
+    Put { key: Vec<u8>, value: Vec<u8> },
+    /// Deletes the entry for `key`, if there is one.
+    Delete { key: Vec<u8> },
+}
+
+/// Number and size of the keys in a namespace, see `StorageBackend::namespace_usage`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NamespaceUsage {
+    pub keys: u64,
+    /// Size of the keys (without namespace) and values in bytes, as stored. The
+    /// RocksDB backend only estimates it, see `RocksDbFacade::namespace_usage`.
+    pub bytes: u64,
+}
+
+/// Operations a key-value store has to provide to be used by `DbService`.
+///
+/// Keys passed to a backend are already namespaced by the service layer, so a
+/// backend only needs to offer a flat, ordered key space. A backend may still
+/// keep namespaces apart by decoding keys with `keys::split_namespace_key`, as
+/// long as listing returns them in the order of the full keys. Keys and values are
+/// plain bytes; encoding and decoding them is up to the service layer, so that
+/// a single damaged entry cannot make a backend operation fail.
+pub trait StorageBackend: std::fmt::Debug + Send {
+    /// Opens the database at `path`. Opening an already opened database is a no-op.
+    fn open_db(&mut self, path: &str) -> Result<(), FacadeError>;
+
+    /// Closes the database, if one is opened.
+    fn close_db(&mut self) -> Result<(), FacadeError>;
+
+    /// Closes the database and removes all of its data.
+    fn destroy_db(&mut self, path: &str) -> Result<(), FacadeError>;
+
+    /// Writes `value` under `key`, overwriting any previous value.
+    fn write_db(&mut self, key: &[u8], value: &[u8]) -> Result<(), FacadeError>;
+
+    /// Reads the value stored under `key`.
+    ///
+    /// Fails with `FacadeError::NotFound` if there is no entry for `key`.
+    fn read_db(&mut self, key: &[u8]) -> Result<Vec<u8>, FacadeError>;
+
+    /// Deletes the entry for `key`.
+    fn delete_db(&mut self, key: &[u8]) -> Result<(), FacadeError>;
+
+    /// Applies all `operations` in the given order, or none of them if any fails.
+    fn write_batch(&mut self, operations: &[BatchOperation]) -> Result<(), FacadeError>;
+
+    /// Lists all keys in the database.
+    fn list_all_keys(&mut self) -> Result<Vec<Vec<u8>>, FacadeError>;
+
+    /// Lists all keys starting with `prefix`.
+    fn list_keys_with_prefix(&mut self, prefix: &[u8]) -> Result<Vec<Vec<u8>>, FacadeError>;
+
+    /// Lists the namespaces that contain at least one key, sorted by name.
+    fn list_namespaces(&mut self) -> Result<Vec<String>, FacadeError>;
+
+    /// Counts the keys in `namespace` and their size. A backend may estimate
+    /// both rather than read every key, so that the call stays cheap.
+    fn namespace_usage(&mut self, namespace: &str) -> Result<NamespaceUsage, FacadeError>;
+
+    /// Deletes all keys in `namespace`, or none of them if that fails.
+    fn drop_namespace(&mut self, namespace: &str) -> Result<(), FacadeError>;
+}
+
+/// Storage backends the server can be started with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BackendKind {
+    /// Persistent storage in a RocksDB database.
+    #[value(name = "rocksdb")]
+    RocksDb,
+    /// Volatile storage in memory, e.g. for tests or a guest mode.
+    Memory,
+}
+
+impl BackendKind {
+    /// Creates a new, unopened backend of this kind.
+    ///
+    /// `rocksdb_config` is only used by the RocksDB backend.
+    pub fn create(self, rocksdb_config: &RocksDbConfig) -> Box<dyn StorageBackend> {
+        match self {
+            BackendKind::RocksDb => Box::new(RocksDbFacade::with_config(rocksdb_config.clone())),
+            BackendKind::Memory => Box::new(MemoryFacade::new()),
+        }
+    }
+}
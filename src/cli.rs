@@ -0,0 +1,66 @@
+use clap::Parser;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+
+use crate::backend::BackendKind;
+
+/// Command-line arguments of the server binary.
+///
+/// Every option can also be set through the environment variable listed in
+/// its help text. Options that are given override the configuration file.
+#[derive(Parser, Debug)]
+#[command(version, about = "Persistent storage API for the AGL demo")]
+pub struct Args {
+    /// Configuration file. Defaults to /etc/agl-persistent-storage/config.toml if it exists.
+    #[arg(long, env = "AGL_PERSISTENT_STORAGE_CONFIG")]
+    pub config: Option<PathBuf>,
+
+    /// Address the gRPC server listens on [default: 127.0.0.1:50054].
+    #[arg(long, env = "AGL_PERSISTENT_STORAGE_LISTEN")]
+    pub listen: Option<SocketAddr>,
+
+    /// Do not listen on the TCP address, e.g. when only the Unix socket should be used.
+    #[arg(long)]
+    pub no_tcp: bool,
+
+    /// Unix domain socket to listen on, in addition to TCP.
+    #[arg(long, env = "AGL_PERSISTENT_STORAGE_UNIX_SOCKET")]
+    pub unix_socket: Option<PathBuf>,
+
+    /// Octal file permissions of the Unix domain socket [default: 660].
+    #[arg(long, env = "AGL_PERSISTENT_STORAGE_UNIX_SOCKET_MODE", value_parser = parse_mode)]
+    pub unix_socket_mode: Option<u32>,
+
+    /// PEM file with the certificate chain for TLS on the TCP listener.
+    #[arg(long, env = "AGL_PERSISTENT_STORAGE_TLS_CERT", requires = "tls_key")]
+    pub tls_cert: Option<PathBuf>,
+
+    /// PEM file with the private key for TLS on the TCP listener.
+    #[arg(long, env = "AGL_PERSISTENT_STORAGE_TLS_KEY", requires = "tls_cert")]
+    pub tls_key: Option<PathBuf>,
+
+    /// PEM file with the CA certificates to verify client certificates against (mutual TLS).
+    #[arg(long, env = "AGL_PERSISTENT_STORAGE_TLS_CLIENT_CA")]
+    pub tls_client_ca: Option<PathBuf>,
+
+    /// Directory of the database files [default: ~/AGLPersistentStorageAPI].
+    #[arg(long, env = "AGL_PERSISTENT_STORAGE_DB_PATH")]
+    pub db_path: Option<String>,
+
+    /// Storage backend the data is kept in [default: rocksdb].
+    #[arg(long, value_enum, env = "AGL_PERSISTENT_STORAGE_BACKEND")]
+    pub backend: Option<BackendKind>,
+
+    /// Log filter, e.g. `info` or `server=debug` [default: info].
+    #[arg(long, env = "AGL_PERSISTENT_STORAGE_LOG_LEVEL")]
+    pub log_level: Option<String>,
+
+    /// Do not register the gRPC reflection service.
+    #[arg(long)]
+    pub no_reflection: bool,
+}
+
+fn parse_mode(mode: &str) -> Result<u32, String> {
+    u32::from_str_radix(mode.trim_start_matches("0o"), 8)
+        .map_err(|_| format!("'{mode}' is not an octal file mode"))
+}
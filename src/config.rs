@@ -0,0 +1,526 @@
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+
+use crate::access::{Operation, Permission};
+use crate::backend::BackendKind;
+use crate::cli::Args;
+use crate::service::default_db_path;
+
+/// Configuration file that is read if no other file is given on the command line.
+pub const DEFAULT_CONFIG_PATH: &str = "/etc/agl-persistent-storage/config.toml";
+
+/// Configuration of the server.
+///
+/// Values are taken from the configuration file first, then overridden by
+/// environment variables and command-line options (see `Args`).
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub server: ServerConfig,
+    pub database: DatabaseConfig,
+    /// Policies for individual namespaces, keyed by namespace name.
+    pub namespaces: BTreeMap<String, NamespacePolicy>,
+    pub defaults: DefaultsConfig,
+    pub access: AccessConfig,
+    pub auth: AuthConfig,
+    pub logging: LoggingConfig,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    /// Address the gRPC server listens on.
+    pub listen: SocketAddr,
+    /// Whether the server listens on the TCP address at all.
+    pub tcp: bool,
+    /// Unix domain socket the server listens on in addition to (or instead of) TCP.
+    pub unix_socket: Option<UnixSocketConfig>,
+    /// Whether the gRPC reflection service is registered.
+    pub reflection: bool,
+    /// TLS for the TCP listener. Plain text is used if unset.
+    pub tls: Option<TlsConfig>,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        ServerConfig {
+            listen: "127.0.0.1:50054".parse().unwrap(),
+            tcp: true,
+            unix_socket: None,
+            reflection: true,
+            tls: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UnixSocketConfig {
+    pub path: PathBuf,
+    /// File permissions of the socket, e.g. `0o660`.
+    #[serde(default = "default_socket_mode")]
+    pub mode: u32,
+}
+
+fn default_socket_mode() -> u32 {
+    0o660
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TlsConfig {
+    /// PEM file with the certificate chain of the server.
+    pub cert: PathBuf,
+    /// PEM file with the private key of the server.
+    pub key: PathBuf,
+    /// PEM file with the CA certificates that client certificates are verified
+    /// against. If set, every TCP client has to present a certificate (mutual TLS).
+    pub client_ca: Option<PathBuf>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DatabaseConfig {
+    /// Directory of the database files.
+    pub path: String,
+    pub backend: BackendKind,
+    /// Seconds between two removals of expired keys; 0 disables them, so
+    /// that expired keys are only hidden.
+    pub expiry_sweep_interval_secs: u64,
+    pub rocksdb: RocksDbConfig,
+}
+
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        DatabaseConfig {
+            path: default_db_path(),
+            backend: BackendKind::RocksDb,
+            expiry_sweep_interval_secs: 300,
+            rocksdb: RocksDbConfig::default(),
+        }
+    }
+}
+
+/// Tuning options passed on to RocksDB. Unset options keep the RocksDB defaults.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RocksDbConfig {
+    pub max_open_files: Option<i32>,
+    pub write_buffer_size: Option<usize>,
+    pub max_write_buffer_number: Option<i32>,
+    pub max_background_jobs: Option<i32>,
+    pub bytes_per_sync: Option<u64>,
+    pub compression: Option<Compression>,
+    /// Options for the column families of individual namespaces, keyed by
+    /// namespace name. They override the options above for that namespace.
+    pub namespaces: BTreeMap<String, ColumnFamilyConfig>,
+}
+
+/// Tuning options of the column family that stores one namespace.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ColumnFamilyConfig {
+    pub write_buffer_size: Option<usize>,
+    pub max_write_buffer_number: Option<i32>,
+    pub compression: Option<Compression>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Compression {
+    None,
+    Snappy,
+    Lz4,
+    Zstd,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NamespacePolicy {
+    /// Rejects all writes and deletions in the namespace.
+    pub read_only: bool,
+    /// Maximum number of keys in the namespace.
+    pub max_keys: Option<u64>,
+    /// Maximum size of all keys and values in the namespace in bytes, counted
+    /// like `max_value_size`.
+    pub max_bytes: Option<u64>,
+    /// Maximum size of a single value in bytes as clients send it, without the
+    /// version, times and type stored with it (see `Value::size`).
+    pub max_value_size: Option<u64>,
+    /// Maximum length of a key in bytes.
+    pub max_key_length: Option<u64>,
+}
+
+impl NamespacePolicy {
+    /// Whether the policy limits the number or total size of the keys, which
+    /// requires tracking the usage of the namespace.
+    pub fn limits_usage(&self) -> bool {
+        self.max_keys.is_some() || self.max_bytes.is_some()
+    }
+}
+
+/// Access control for clients connected through the Unix domain socket.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AccessConfig {
+    /// Enforces the rules. If disabled, every client may access every namespace.
+    pub enabled: bool,
+    pub rules: Vec<AccessRule>,
+}
+
+/// Grants a permission on some namespaces to the clients matching `uid` and `gid`.
+///
+/// A rule without `uid` and `gid` matches every Unix socket client. A rule with
+/// `common_name` only matches TCP clients with that client certificate instead.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AccessRule {
+    pub uid: Option<u32>,
+    pub gid: Option<u32>,
+    /// Common name (CN) in the subject of the client certificate.
+    pub common_name: Option<String>,
+    /// Namespaces the rule applies to; `"*"` matches every namespace.
+    pub namespaces: Vec<String>,
+    pub permission: Permission,
+}
+
+/// Factory defaults written at the first startup of a namespace to the keys
+/// that do not exist.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DefaultsConfig {
+    /// VSS specification (YAML, or JSON if the name ends in `.json`) with the
+    /// default values. Nothing is written if unset.
+    pub file: Option<PathBuf>,
+    /// Namespaces that get the default values.
+    pub namespaces: Vec<String>,
+    /// Whether read-only namespaces among `namespaces` get the default values
+    /// too. They are skipped with a note in the log otherwise.
+    pub write_read_only: bool,
+}
+
+impl Default for DefaultsConfig {
+    fn default() -> Self {
+        DefaultsConfig {
+            file: None,
+            namespaces: vec![String::new()],
+            write_read_only: false,
+        }
+    }
+}
+
+/// Bearer token authentication for clients connected through TCP.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AuthConfig {
+    /// Rejects TCP requests without a valid token with `UNAUTHENTICATED`.
+    pub enabled: bool,
+    pub tokens: Vec<TokenConfig>,
+}
+
+/// Token accepted from TCP clients and the permission it grants.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TokenConfig {
+    /// Name of the token holder, used in log messages instead of the token.
+    pub name: String,
+    pub token: String,
+    /// Namespaces the token grants access to; `"*"` matches every namespace.
+    pub namespaces: Vec<String>,
+    pub permission: Permission,
+    /// Calls the token grants, e.g. `["Read", "DestroyDB"]`, as far as
+    /// `permission` allows them. All of them if empty.
+    #[serde(default)]
+    pub operations: Vec<Operation>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LoggingConfig {
+    /// Log filter in `env_logger` syntax, e.g. `info` or `server=debug`.
+    pub level: String,
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        LoggingConfig {
+            level: String::from("info"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Read(PathBuf, std::io::Error),
+    Parse(PathBuf, toml::de::Error),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Read(path, e) => write!(
+                f,
+                "Unable to read configuration file '{}': {}",
+                path.display(),
+                e
+            ),
+            ConfigError::Parse(path, e) => write!(
+                f,
+                "Malformed configuration file '{}': {}",
+                path.display(),
+                e
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl Config {
+    /// Loads the configuration file and applies the overrides from `args`.
+    ///
+    /// The file given in `args` has to exist; the default file is optional.
+    pub fn load(args: &Args) -> Result<Config, ConfigError> {
+        let mut config = match &args.config {
+            Some(path) => Config::from_file(path)?,
+            None if Path::new(DEFAULT_CONFIG_PATH).exists() => {
+                Config::from_file(Path::new(DEFAULT_CONFIG_PATH))?
+            }
+            None => Config::default(),
+        };
+        config.apply_args(args);
+        Ok(config)
+    }
+
+    pub fn from_file(path: &Path) -> Result<Config, ConfigError> {
+        let content =
+            std::fs::read_to_string(path).map_err(|e| ConfigError::Read(path.to_path_buf(), e))?;
+        toml::from_str(&content).map_err(|e| ConfigError::Parse(path.to_path_buf(), e))
+    }
+
+    fn apply_args(&mut self, args: &Args) {
+        if let Some(listen) = args.listen {
+            self.server.listen = listen;
+        }
+        if args.no_tcp {
+            self.server.tcp = false;
+        }
+        if let Some(path) = &args.unix_socket {
+            match &mut self.server.unix_socket {
+                Some(socket) => socket.path = path.clone(),
+                None => {
+                    self.server.unix_socket = Some(UnixSocketConfig {
+                        path: path.clone(),
+                        mode: default_socket_mode(),
+                    })
+                }
+            }
+        }
+        if let (Some(socket), Some(mode)) = (&mut self.server.unix_socket, args.unix_socket_mode) {
+            socket.mode = mode;
+        }
+        if let (Some(cert), Some(key)) = (&args.tls_cert, &args.tls_key) {
+            let client_ca = self.server.tls.take().and_then(|tls| tls.client_ca);
+            self.server.tls = Some(TlsConfig {
+                cert: cert.clone(),
+                key: key.clone(),
+                client_ca,
+            });
+        }
+        if let (Some(tls), Some(client_ca)) = (&mut self.server.tls, &args.tls_client_ca) {
+            tls.client_ca = Some(client_ca.clone());
+        }
+        if args.no_reflection {
+            self.server.reflection = false;
+        }
+        if let Some(db_path) = &args.db_path {
+            self.database.path = db_path.clone();
+        }
+        if let Some(backend) = args.backend {
+            self.database.backend = backend;
+        }
+        if let Some(log_level) = &args.log_level {
+            self.logging.level = log_level.clone();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::*;
+    use clap::Parser;
+
+    #[test]
+    fn parse_full_config_test() {
+        let config: Config = toml::from_str(
+            r#"
+            [server]
+            listen = "0.0.0.0:50060"
+            reflection = false
+
+            [server.unix_socket]
+            path = "/run/agl-persistent-storage/storage.sock"
+
+            [server.tls]
+            cert = "/etc/agl-persistent-storage/server.pem"
+            key = "/etc/agl-persistent-storage/server.key"
+
+            [database]
+            path = "/var/lib/agl-storage"
+            backend = "memory"
+            expiry_sweep_interval_secs = 60
+
+            [database.rocksdb]
+            max_open_files = 64
+            compression = "lz4"
+
+            [database.rocksdb.namespaces.media]
+            write_buffer_size = 16777216
+
+            [namespaces.vehicle-config]
+            read_only = true
+
+            [namespaces.media]
+            max_keys = 1000
+            max_value_size = 4096
+
+            [access]
+            enabled = true
+
+            [[access.rules]]
+            uid = 1001
+            namespaces = ["AppName"]
+            permission = "write"
+
+            [defaults]
+            file = "/usr/share/agl-persistent-storage/defaults.vspec"
+            namespaces = ["", "vehicle-config"]
+            write_read_only = true
+
+            [auth]
+            enabled = true
+
+            [[auth.tokens]]
+            name = "diagnostics"
+            token = "secret"
+            namespaces = ["*"]
+            permission = "admin"
+            operations = ["Read", "ListNamespaces", "DestroyDB"]
+
+            [logging]
+            level = "debug"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.server.listen, "0.0.0.0:50060".parse().unwrap());
+        assert!(!config.server.reflection);
+        assert!(config.server.tcp);
+        let socket = config.server.unix_socket.unwrap();
+        assert_eq!(
+            socket.path,
+            Path::new("/run/agl-persistent-storage/storage.sock")
+        );
+        assert_eq!(socket.mode, 0o660);
+        let tls = config.server.tls.unwrap();
+        assert_eq!(tls.key, Path::new("/etc/agl-persistent-storage/server.key"));
+        assert_eq!(tls.client_ca, None);
+        assert_eq!(config.database.path, "/var/lib/agl-storage");
+        assert_eq!(config.database.backend, BackendKind::Memory);
+        assert_eq!(config.database.expiry_sweep_interval_secs, 60);
+        assert_eq!(config.database.rocksdb.max_open_files, Some(64));
+        assert_eq!(config.database.rocksdb.compression, Some(Compression::Lz4));
+        assert_eq!(
+            config.database.rocksdb.namespaces["media"].write_buffer_size,
+            Some(16777216)
+        );
+        assert!(config.namespaces["vehicle-config"].read_only);
+        assert!(!config.namespaces["vehicle-config"].limits_usage());
+        assert_eq!(config.namespaces["media"].max_keys, Some(1000));
+        assert_eq!(config.namespaces["media"].max_bytes, None);
+        assert!(config.namespaces["media"].limits_usage());
+        assert!(!config.namespaces.contains_key(""));
+        assert_eq!(
+            config.defaults.file.unwrap(),
+            Path::new("/usr/share/agl-persistent-storage/defaults.vspec")
+        );
+        assert_eq!(config.defaults.namespaces, vec!["", "vehicle-config"]);
+        assert!(config.defaults.write_read_only);
+        assert!(config.access.enabled);
+        assert_eq!(config.access.rules[0].uid, Some(1001));
+        assert_eq!(config.access.rules[0].gid, None);
+        assert_eq!(config.access.rules[0].namespaces, vec!["AppName"]);
+        assert_eq!(config.access.rules[0].permission, Permission::Write);
+        assert!(config.auth.enabled);
+        assert_eq!(config.auth.tokens[0].name, "diagnostics");
+        assert_eq!(config.auth.tokens[0].permission, Permission::Admin);
+        assert_eq!(
+            config.auth.tokens[0].operations,
+            vec![
+                Operation::Read,
+                Operation::ListNamespaces,
+                Operation::DestroyDb
+            ]
+        );
+        assert_eq!(config.logging.level, "debug");
+    }
+
+    #[test]
+    fn malformed_config_test() {
+        assert!(toml::from_str::<Config>("[server]\nlisten = 50054\n").is_err());
+        assert!(toml::from_str::<Config>("[database]\nbackend = \"sqlite\"\n").is_err());
+        assert!(toml::from_str::<Config>("[unknown]\n").is_err());
+    }
+
+    #[test]
+    fn args_override_config_test() {
+        let mut config: Config = toml::from_str(
+            r#"
+            [server]
+            listen = "0.0.0.0:50060"
+
+            [server.tls]
+            cert = "/etc/agl-persistent-storage/server.pem"
+            key = "/etc/agl-persistent-storage/server.key"
+
+            [database]
+            path = "/var/lib/agl-storage"
+            "#,
+        )
+        .unwrap();
+        let args = Args::parse_from([
+            "server",
+            "--listen",
+            "127.0.0.1:50070",
+            "--backend",
+            "memory",
+            "--unix-socket",
+            "/tmp/storage.sock",
+            "--unix-socket-mode",
+            "600",
+            "--tls-client-ca",
+            "/etc/agl-persistent-storage/ca.pem",
+        ]);
+
+        config.apply_args(&args);
+
+        assert_eq!(config.server.listen, "127.0.0.1:50070".parse().unwrap());
+        assert_eq!(config.database.path, "/var/lib/agl-storage");
+        assert_eq!(config.database.backend, BackendKind::Memory);
+        let socket = config.server.unix_socket.unwrap();
+        assert_eq!(socket.path, Path::new("/tmp/storage.sock"));
+        assert_eq!(socket.mode, 0o600);
+        let tls = config.server.tls.unwrap();
+        assert_eq!(
+            tls.cert,
+            Path::new("/etc/agl-persistent-storage/server.pem")
+        );
+        assert_eq!(
+            tls.client_ca.unwrap(),
+            Path::new("/etc/agl-persistent-storage/ca.pem")
+        );
+    }
+}
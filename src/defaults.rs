@@ -0,0 +1,284 @@
+use serde_json::Map;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::record::Value;
+use crate::tree;
+
+/// Reason why a VSS specification could not be loaded.
+#[derive(Debug)]
+pub enum SpecError {
+    Read(PathBuf, std::io::Error),
+    Parse(PathBuf, String),
+}
+
+impl fmt::Display for SpecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SpecError::Read(path, e) => write!(
+                f,
+                "Unable to read VSS specification '{}': {}",
+                path.display(),
+                e
+            ),
+            SpecError::Parse(path, e) => {
+                write!(f, "Malformed VSS specification '{}': {}", path.display(), e)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SpecError {}
+
+/// Reads the default values from the VSS specification at `path`, which is
+/// JSON if its name ends in `.json` and YAML otherwise. See `default_values`.
+pub fn load(path: &Path) -> Result<BTreeMap<String, Value>, SpecError> {
+    let content =
+        std::fs::read_to_string(path).map_err(|e| SpecError::Read(path.to_path_buf(), e))?;
+    let spec: serde_json::Value = if path
+        .extension()
+        .is_some_and(|extension| extension == "json")
+    {
+        serde_json::from_str(&content)
+            .map_err(|e| SpecError::Parse(path.to_path_buf(), e.to_string()))?
+    } else {
+        serde_yaml::from_str(&content)
+            .map_err(|e| SpecError::Parse(path.to_path_buf(), e.to_string()))?
+    };
+    match spec {
+        serde_json::Value::Object(members) => Ok(default_values(&members)),
+        _ => Err(SpecError::Parse(
+            path.to_path_buf(),
+            String::from("The specification must be a map of nodes"),
+        )),
+    }
+}
+
+/// Collects the `default` of every node in a VSS specification, keyed by the
+/// full name of the node.
+///
+/// Both the flat form of `.vspec` files, in which every node is a member named
+/// by its full name, and the nested form of the JSON exporter, in which a
+/// branch holds its nodes in `children`, are read. The `datatype` of a node
+/// decides the type of its value. Defaults that cannot be stored, e.g. lists
+/// of numbers, are skipped with a warning.
+pub fn default_values(spec: &Map<String, serde_json::Value>) -> BTreeMap<String, Value> {
+    let mut defaults = BTreeMap::new();
+    collect(spec, "", &mut defaults);
+    defaults
+}
+
+fn collect(
+    nodes: &Map<String, serde_json::Value>,
+    parent: &str,
+    defaults: &mut BTreeMap<String, Value>,
+) {
+    for (name, node) in nodes {
+        let node = match node {
+            serde_json::Value::Object(node) => node,
+            _ => continue,
+        };
+        let key = if parent.is_empty() {
+            name.clone()
+        } else {
+            parent.to_string() + "." + name
+        };
+        if let Some(default) = node.get("default") {
+            let datatype = node.get("datatype").and_then(serde_json::Value::as_str);
+            match default_value(datatype, default) {
+                Ok(value) => {
+                    defaults.insert(key.clone(), value);
+                }
+                Err(message) => log::warn!("Skipping default of '{key}': {message}"),
+            }
+        }
+        if let Some(serde_json::Value::Object(children)) = node.get("children") {
+            collect(children, &key, defaults);
+        }
+    }
+}
+
+/// Converts the default of a node with the VSS `datatype` to a value. Without
+/// a datatype, the type follows from the default, as in `tree::from_tree`.
+fn default_value(datatype: Option<&str>, default: &serde_json::Value) -> Result<Value, String> {
+    let mismatch = || {
+        String::from("Default ")
+            + &default.to_string()
+            + " is not of type "
+            + datatype.unwrap_or_default()
+    };
+    match datatype {
+        Some("string") => match default {
+            serde_json::Value::String(value) => Ok(Value::String(value.clone())),
+            _ => Err(mismatch()),
+        },
+        Some("boolean") => default.as_bool().map(Value::Bool).ok_or_else(mismatch),
+        Some(
+            datatype @ ("int8" | "int16" | "int32" | "int64" | "uint8" | "uint16" | "uint32"
+            | "uint64"),
+        ) => {
+            let (min, max) = int_range(datatype);
+            match default.as_i64() {
+                Some(value) if (min..=max).contains(&value) => Ok(Value::Int(value)),
+                Some(_) => Err(String::from("Default ")
+                    + &default.to_string()
+                    + " is out of range for "
+                    + datatype),
+                // Ints are stored as int64, which cannot hold the upper half of uint64.
+                None if default.is_u64() => Err(String::from("Default ")
+                    + &default.to_string()
+                    + " is larger than the largest int that can be stored"),
+                None => Err(mismatch()),
+            }
+        }
+        Some("float" | "double") => default.as_f64().map(Value::Double).ok_or_else(mismatch),
+        Some("string[]") | None => tree::value_from_json(default),
+        Some(datatype) => Err(String::from("Datatype ") + datatype + " is not supported"),
+    }
+}
+
+/// Returns the smallest and largest value of the VSS int `datatype` that can be
+/// stored, which for uint64 ends at the largest int64.
+fn int_range(datatype: &str) -> (i64, i64) {
+    match datatype {
+        "int8" => (i8::MIN.into(), i8::MAX.into()),
+        "int16" => (i16::MIN.into(), i16::MAX.into()),
+        "int32" => (i32::MIN.into(), i32::MAX.into()),
+        "uint8" => (0, u8::MAX.into()),
+        "uint16" => (0, u16::MAX.into()),
+        "uint32" => (0, u32::MAX.into()),
+        "uint64" => (0, i64::MAX),
+        _ => (i64::MIN, i64::MAX),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::defaults::*;
+
+    #[test]
+    fn default_values_test() {
+        let flat: serde_json::Value = serde_yaml::from_str(
+            r#"
+            Vehicle:
+              type: branch
+              description: High-level vehicle data.
+
+            Vehicle.Cabin.HVAC.Station.Row1.Left.FanSpeed:
+              datatype: uint8
+              type: actuator
+              default: 40
+
+            Vehicle.Cabin.HVAC.IsAirConditioningActive:
+              datatype: boolean
+              type: actuator
+              default: false
+
+            Vehicle.Cabin.Infotainment.Media.Volume:
+              datatype: float
+              type: actuator
+              default: 20
+
+            Vehicle.Cabin.Infotainment.Navigation.DestinationSet:
+              datatype: uint8[]
+              type: actuator
+              default: [1, 2]
+
+            Vehicle.Cabin.Infotainment.HMI.DistanceUnit:
+              datatype: string
+              type: actuator
+              default: KILOMETERS
+
+            Vehicle.Cabin.Infotainment.HMI.TimeFormat:
+              datatype: string
+              type: actuator
+              allowed: ['HR_12', 'HR_24']
+
+            Vehicle.Cabin.Infotainment.HMI.LastActionTime:
+              datatype: uint64
+              type: sensor
+              default: 18446744073709551615
+
+            Vehicle.Cabin.Infotainment.HMI.Brightness:
+              datatype: uint8
+              type: actuator
+              default: -1
+
+            Vehicle.Cabin.Infotainment.HMI.FontSize:
+              datatype: int8
+              type: actuator
+              default: 200
+
+            Vehicle.Cabin.Infotainment.HMI.CurrentLanguage:
+              datatype: string
+              type: actuator
+              default: 1
+
+            Vehicle.Cabin.Infotainment.HMI.DayNightMode:
+              datatype: string
+              type: actuator
+              default: true
+
+            Vehicle.OBD.Odometer:
+              datatype: uint64
+              type: sensor
+              default: 9223372036854775807
+            "#,
+        )
+        .unwrap();
+        let nested = serde_json::json!({
+            "Vehicle": {
+                "type": "branch",
+                "children": {
+                    "Cabin": {
+                        "type": "branch",
+                        "children": {
+                            "Presets": {
+                                "datatype": "string[]",
+                                "default": ["1live", "WDR 2"]
+                            },
+                            "Seat": {
+                                "datatype": "uint8",
+                                "default": "high"
+                            }
+                        }
+                    }
+                }
+            }
+        });
+
+        let defaults_flat = default_values(flat.as_object().unwrap());
+        let defaults_nested = default_values(nested.as_object().unwrap());
+
+        assert_eq!(
+            defaults_flat,
+            BTreeMap::from([
+                (
+                    "Vehicle.Cabin.HVAC.IsAirConditioningActive".to_string(),
+                    Value::Bool(false)
+                ),
+                (
+                    "Vehicle.Cabin.HVAC.Station.Row1.Left.FanSpeed".to_string(),
+                    Value::Int(40)
+                ),
+                (
+                    "Vehicle.Cabin.Infotainment.HMI.DistanceUnit".to_string(),
+                    Value::from("KILOMETERS")
+                ),
+                (
+                    "Vehicle.Cabin.Infotainment.Media.Volume".to_string(),
+                    Value::Double(20.0)
+                ),
+                ("Vehicle.OBD.Odometer".to_string(), Value::Int(i64::MAX)),
+            ])
+        );
+        assert_eq!(
+            defaults_nested,
+            BTreeMap::from([(
+                "Vehicle.Cabin.Presets".to_string(),
+                Value::StringList(vec!["1live".to_string(), "WDR 2".to_string()])
+            )])
+        );
+    }
+}
@@ -1,126 +1,461 @@
-use rust_rocksdb::{Options, DB};
+use rust_rocksdb::{
+    properties, ColumnFamily, DBCompressionType, ErrorKind, Options, WriteBatch, DB,
+    DEFAULT_COLUMN_FAMILY_NAME,
+};
+use std::collections::BTreeSet;
+use std::fmt;
+use std::path::Path;
 
+use crate::backend::{BatchOperation, NamespaceUsage, StorageBackend};
+use crate::config::{Compression, RocksDbConfig};
+use crate::keys;
+
+/// Reason why an operation of a storage backend failed.
+#[derive(Debug)]
+pub enum FacadeError {
+    /// No database is opened.
+    NotOpen,
+    /// There is no entry for the key.
+    NotFound,
+    /// RocksDB reported an error.
+    Backend(rust_rocksdb::Error),
+    /// A stored key or value is not valid UTF-8.
+    InvalidUtf8(std::str::Utf8Error),
+    /// The stored data is damaged.
+    Corruption(String),
+}
+
+impl fmt::Display for FacadeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FacadeError::NotOpen => write!(f, "No database opened"),
+            FacadeError::NotFound => write!(f, "Key not found"),
+            FacadeError::Backend(e) => write!(f, "{e}"),
+            FacadeError::InvalidUtf8(e) => write!(f, "Stored data is not valid UTF-8: {e}"),
+            FacadeError::Corruption(message) => write!(f, "Corrupted database: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for FacadeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            FacadeError::Backend(e) => Some(e),
+            FacadeError::InvalidUtf8(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<rust_rocksdb::Error> for FacadeError {
+    fn from(error: rust_rocksdb::Error) -> FacadeError {
+        match error.kind() {
+            ErrorKind::Corruption => FacadeError::Corruption(error.into_string()),
+            _ => FacadeError::Backend(error),
+        }
+    }
+}
+
+impl From<std::str::Utf8Error> for FacadeError {
+    fn from(error: std::str::Utf8Error) -> FacadeError {
+        FacadeError::InvalidUtf8(error)
+    }
+}
+
+/// Prefix of the names of the column families that hold the namespaces.
+const NAMESPACE_CF_PREFIX: &str = "namespace:";
+
+/// Returns the name of the column family that holds `namespace`.
+fn column_family_name(namespace: &str) -> String {
+    String::from(NAMESPACE_CF_PREFIX) + namespace
+}
+
+fn compression_type(compression: Compression) -> DBCompressionType {
+    match compression {
+        Compression::None => DBCompressionType::None,
+        Compression::Snappy => DBCompressionType::Snappy,
+        Compression::Lz4 => DBCompressionType::Lz4,
+        Compression::Zstd => DBCompressionType::Zstd,
+    }
+}
+
+/// Storage backend on RocksDB.
+///
+/// Every namespace is stored in a column family of its own, so that it can be
+/// tuned separately and dropped as a whole. Its keys are stored there without
+/// the namespace prefix. Keys that are not namespaced (see
+/// `keys::split_namespace_key`) are stored in the default column family.
 #[derive(Debug)]
 pub struct RocksDbFacade {
     db_instance: Option<DB>,
+    config: RocksDbConfig,
+    /// Namespaces that have a column family in the opened database.
+    namespaces: BTreeSet<String>,
 }
 
 impl RocksDbFacade {
     pub fn new() -> RocksDbFacade {
-        RocksDbFacade { db_instance: None }
+        RocksDbFacade::with_config(RocksDbConfig::default())
     }
 
-    pub fn open_db(&mut self, path: &str) -> Result<(), std::io::Error> {
-        if self.db_instance.is_some() {
+    /// Creates a facade that opens the database with the given tuning options.
+    pub fn with_config(config: RocksDbConfig) -> RocksDbFacade {
+        RocksDbFacade {
+            db_instance: None,
+            config,
+            namespaces: BTreeSet::new(),
+        }
+    }
+
+    fn options(&self) -> Options {
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        if let Some(max_open_files) = self.config.max_open_files {
+            opts.set_max_open_files(max_open_files);
+        }
+        if let Some(write_buffer_size) = self.config.write_buffer_size {
+            opts.set_write_buffer_size(write_buffer_size);
+        }
+        if let Some(max_write_buffer_number) = self.config.max_write_buffer_number {
+            opts.set_max_write_buffer_number(max_write_buffer_number);
+        }
+        if let Some(max_background_jobs) = self.config.max_background_jobs {
+            opts.set_max_background_jobs(max_background_jobs);
+        }
+        if let Some(bytes_per_sync) = self.config.bytes_per_sync {
+            opts.set_bytes_per_sync(bytes_per_sync);
+        }
+        if let Some(compression) = self.config.compression {
+            opts.set_compression_type(compression_type(compression));
+        }
+        opts
+    }
+
+    /// Returns the options of the column family of `namespace`: the database
+    /// options with the overrides configured for the namespace.
+    fn namespace_options(&self, namespace: &str) -> Options {
+        let mut opts = self.options();
+        if let Some(config) = self.config.namespaces.get(namespace) {
+            if let Some(write_buffer_size) = config.write_buffer_size {
+                opts.set_write_buffer_size(write_buffer_size);
+            }
+            if let Some(max_write_buffer_number) = config.max_write_buffer_number {
+                opts.set_max_write_buffer_number(max_write_buffer_number);
+            }
+            if let Some(compression) = config.compression {
+                opts.set_compression_type(compression_type(compression));
+            }
+        }
+        opts
+    }
+
+    fn db_instance(&self) -> Result<&DB, FacadeError> {
+        self.db_instance.as_ref().ok_or(FacadeError::NotOpen)
+    }
+
+    /// Returns the column family of `namespace`, or `None` if the namespace
+    /// has never been written to.
+    fn column_family<'a>(db: &'a DB, namespace: &str) -> Option<&'a ColumnFamily> {
+        db.cf_handle(&column_family_name(namespace))
+    }
+
+    /// Creates the column family of `namespace` unless it already exists.
+    fn create_column_family(&mut self, namespace: &str) -> Result<(), FacadeError> {
+        if self.namespaces.contains(namespace) {
             return Ok(());
-        } else {
-            let mut opts = Options::default();
-            opts.create_if_missing(true);
-            self.db_instance = Some(
-                DB::open(&opts, path)
-                    .map_err(|error| std::io::Error::new(std::io::ErrorKind::Other, error))?,
+        }
+        let opts = self.namespace_options(namespace);
+        self.db_instance
+            .as_mut()
+            .ok_or(FacadeError::NotOpen)?
+            .create_cf(column_family_name(namespace), &opts)?;
+        self.namespaces.insert(namespace.to_string());
+        Ok(())
+    }
+
+    /// Moves namespaced keys from the default column family, where they were
+    /// stored before every namespace had a column family of its own, to the
+    /// column families of their namespaces in a single batch.
+    ///
+    /// The batch records `keys::COLUMN_FAMILY_LAYOUT_KEY`, so the default
+    /// column family is only scanned once, like `keys::migrate` does with the
+    /// key format. An interrupted move is retried as a whole on the next open.
+    fn move_namespaced_keys(&mut self) -> Result<(), FacadeError> {
+        if self
+            .db_instance()?
+            .get(keys::COLUMN_FAMILY_LAYOUT_KEY)?
+            .is_some()
+        {
+            return Ok(());
+        }
+        let mut iter = self.db_instance()?.raw_iterator();
+        iter.seek_to_first();
+        let namespaced_keys: Vec<Vec<u8>> = RocksDbFacade::collect_keys(iter, b"")?
+            .into_iter()
+            .filter(|key| keys::split_namespace_key(key).is_some())
+            .collect();
+        for key in &namespaced_keys {
+            let (namespace, _) = keys::split_namespace_key(key).expect("namespaced key");
+            self.create_column_family(namespace)?;
+        }
+
+        let db = self.db_instance()?;
+        let mut batch = WriteBatch::default();
+        for key in &namespaced_keys {
+            let (namespace, stripped_key) = keys::split_namespace_key(key).expect("namespaced key");
+            let value = db.get(key)?.ok_or(FacadeError::NotFound)?;
+            let cf = RocksDbFacade::column_family(db, namespace)
+                .expect("column family was created above");
+            batch.put_cf(cf, stripped_key, value);
+            batch.delete(key);
+        }
+        batch.put(keys::COLUMN_FAMILY_LAYOUT_KEY, b"");
+        db.write(batch)?;
+        if !namespaced_keys.is_empty() {
+            log::info!(
+                "Moved {} keys to the column families of their namespaces",
+                namespaced_keys.len()
             );
+        }
+        Ok(())
+    }
+
+    /// Collects the keys from `iter` onwards for as long as they start with `prefix`.
+    fn collect_keys(
+        mut iter: rust_rocksdb::DBRawIteratorWithThreadMode<'_, DB>,
+        prefix: &[u8],
+    ) -> Result<Vec<Vec<u8>>, FacadeError> {
+        let mut res: Vec<Vec<u8>> = Vec::new();
+        while iter.valid() {
+            let key_u8 = iter.key().ok_or(FacadeError::Corruption(String::from(
+                "Found key of type None",
+            )))?;
+            if !key_u8.starts_with(prefix) {
+                break;
+            }
+            res.push(key_u8.to_vec());
+            iter.next();
+        }
+        iter.status()?;
+        Ok(res)
+    }
+}
+
+impl StorageBackend for RocksDbFacade {
+    fn open_db(&mut self, path: &str) -> Result<(), FacadeError> {
+        if self.db_instance.is_some() {
             return Ok(());
         }
+        let opts = self.options();
+        // A database that does not exist yet only gets the default column
+        // family. Failing to list those of an existing one is an error, as
+        // opening it without them would fail or hide its namespaces.
+        let column_families = if Path::new(path).join("CURRENT").exists() {
+            DB::list_cf(&opts, path)?
+        } else {
+            vec![String::from(DEFAULT_COLUMN_FAMILY_NAME)]
+        };
+        let descriptors: Vec<(String, Options)> = column_families
+            .into_iter()
+            .map(|name| {
+                let cf_opts = match name.strip_prefix(NAMESPACE_CF_PREFIX) {
+                    Some(namespace) => self.namespace_options(namespace),
+                    None => opts.clone(),
+                };
+                (name, cf_opts)
+            })
+            .collect();
+        self.namespaces = descriptors
+            .iter()
+            .filter_map(|(name, _)| name.strip_prefix(NAMESPACE_CF_PREFIX))
+            .map(str::to_string)
+            .collect();
+        self.db_instance = Some(DB::open_cf_with_opts(&opts, path, descriptors)?);
+        self.move_namespaced_keys()
     }
 
-    pub fn close_db(&mut self) -> Result<(), std::io::Error> {
+    fn close_db(&mut self) -> Result<(), FacadeError> {
         drop(self.db_instance.take());
+        self.namespaces.clear();
         Ok(())
     }
 
-    pub fn destroy_db(&mut self, path: &str) -> Result<(), std::io::Error> {
+    fn destroy_db(&mut self, path: &str) -> Result<(), FacadeError> {
         self.close_db()?;
-        DB::destroy(&Options::default(), path)
-            .map_err(|error| std::io::Error::new(std::io::ErrorKind::Other, error))
-    }
-
-    pub fn write_db(&mut self, key: &str, value: &str) -> Result<(), std::io::Error> {
-        let db_instance = self.db_instance.as_ref().ok_or(std::io::Error::new(
-            std::io::ErrorKind::Other,
-            "No database opened",
-        ))?;
-        db_instance
-            .put(key, value)
-            .map_err(|error| std::io::Error::new(std::io::ErrorKind::Other, error))
-    }
-
-    pub fn read_db(&mut self, key: &str) -> Result<String, std::io::Error> {
-        let db_instance = self.db_instance.as_ref().ok_or(std::io::Error::new(
-            std::io::ErrorKind::Other,
-            "No database opened",
-        ))?;
-        let res = db_instance
-            .get(key)
-            .map_err(|error| std::io::Error::new(std::io::ErrorKind::Other, error))?;
-        let value = res.ok_or(std::io::Error::new(
-            std::io::ErrorKind::Other,
-            "Key not found",
-        ))?;
-        Ok(String::from_utf8_lossy(&value).to_string())
-    }
-
-    pub fn delete_db(&mut self, key: &str) -> Result<(), std::io::Error> {
-        let db_instance = self.db_instance.as_ref().ok_or(std::io::Error::new(
-            std::io::ErrorKind::Other,
-            "No database opened",
-        ))?;
-        db_instance
-            .delete(key)
-            .map_err(|error| std::io::Error::new(std::io::ErrorKind::Other, error))
-    }
-
-    pub fn list_all_keys(&mut self) -> Result<Vec<String>, std::io::Error> {
-        let db_instance = self.db_instance.as_ref().ok_or(std::io::Error::new(
-            std::io::ErrorKind::Other,
-            "No database opened",
-        ))?;
-        let mut iter = db_instance.raw_iterator();
-        let mut res: Vec<String> = Vec::new();
-        iter.seek_to_first();
-        while iter.valid() {
-            let key_u8 = iter.key().ok_or(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                "Found key of type None",
-            ))?;
-            res.push(
-                std::str::from_utf8(key_u8)
-                    .map_err(|error| std::io::Error::new(std::io::ErrorKind::Other, error))?
-                    .to_string(),
+        Ok(DB::destroy(&Options::default(), path)?)
+    }
+
+    fn write_db(&mut self, key: &[u8], value: &[u8]) -> Result<(), FacadeError> {
+        match keys::split_namespace_key(key) {
+            Some((namespace, key)) => {
+                self.create_column_family(namespace)?;
+                let db = self.db_instance()?;
+                let cf = RocksDbFacade::column_family(db, namespace)
+                    .expect("column family was created above");
+                Ok(db.put_cf(cf, key, value)?)
+            }
+            None => Ok(self.db_instance()?.put(key, value)?),
+        }
+    }
+
+    fn read_db(&mut self, key: &[u8]) -> Result<Vec<u8>, FacadeError> {
+        let db = self.db_instance()?;
+        let value = match keys::split_namespace_key(key) {
+            Some((namespace, key)) => match RocksDbFacade::column_family(db, namespace) {
+                Some(cf) => db.get_cf(cf, key)?,
+                None => None,
+            },
+            None => db.get(key)?,
+        };
+        value.ok_or(FacadeError::NotFound)
+    }
+
+    fn delete_db(&mut self, key: &[u8]) -> Result<(), FacadeError> {
+        let db = self.db_instance()?;
+        match keys::split_namespace_key(key) {
+            Some((namespace, key)) => match RocksDbFacade::column_family(db, namespace) {
+                Some(cf) => Ok(db.delete_cf(cf, key)?),
+                None => Ok(()),
+            },
+            None => Ok(db.delete(key)?),
+        }
+    }
+
+    fn write_batch(&mut self, operations: &[BatchOperation]) -> Result<(), FacadeError> {
+        // Column families are created outside of the batch, but an empty
+        // column family is indistinguishable from a missing one.
+        for operation in operations {
+            if let BatchOperation::Put { key, .. } = operation {
+                if let Some((namespace, _)) = keys::split_namespace_key(key) {
+                    self.create_column_family(namespace)?;
+                }
+            }
+        }
+
+        let db_instance = self.db_instance()?;
+        let mut batch = WriteBatch::default();
+        for operation in operations {
+            let (key, value) = match operation {
+                BatchOperation::Put { key, value } => (key, Some(value)),
+                BatchOperation::Delete { key } => (key, None),
+            };
+            match keys::split_namespace_key(key) {
+                Some((namespace, key)) => {
+                    // Only deletions can target a namespace without a column
+                    // family, and there is nothing to delete there.
+                    if let Some(cf) = RocksDbFacade::column_family(db_instance, namespace) {
+                        match value {
+                            Some(value) => batch.put_cf(cf, key, value),
+                            None => batch.delete_cf(cf, key),
+                        }
+                    }
+                }
+                None => match value {
+                    Some(value) => batch.put(key, value),
+                    None => batch.delete(key),
+                },
+            }
+        }
+        Ok(db_instance.write(batch)?)
+    }
+
+    fn list_all_keys(&mut self) -> Result<Vec<Vec<u8>>, FacadeError> {
+        self.list_keys_with_prefix(b"")
+    }
+
+    fn list_keys_with_prefix(&mut self, prefix: &[u8]) -> Result<Vec<Vec<u8>>, FacadeError> {
+        let db = self.db_instance()?;
+        let mut iter = db.raw_iterator();
+        iter.seek(prefix);
+        let mut res = RocksDbFacade::collect_keys(iter, prefix)?;
+        for namespace in &self.namespaces {
+            let namespace_prefix = keys::namespace_prefix(namespace);
+            // Either `prefix` reaches into the keys of the namespace, or all of
+            // them start with it.
+            let key_prefix =
+                if let Some(key_prefix) = prefix.strip_prefix(namespace_prefix.as_slice()) {
+                    key_prefix
+                } else if namespace_prefix.starts_with(prefix) {
+                    b""
+                } else {
+                    continue;
+                };
+            let cf = RocksDbFacade::column_family(db, namespace).ok_or_else(|| {
+                FacadeError::Corruption(format!(
+                    "Column family of namespace '{namespace}' is missing"
+                ))
+            })?;
+            let mut iter = db.raw_iterator_cf(cf);
+            iter.seek(key_prefix);
+            res.extend(
+                RocksDbFacade::collect_keys(iter, key_prefix)?
+                    .into_iter()
+                    .map(|key| [namespace_prefix.as_slice(), &key].concat()),
             );
-            iter.next();
         }
+        res.sort();
         Ok(res)
     }
 
-    pub fn list_keys_with_prefix(&mut self, prefix: &str) -> Result<Vec<String>, std::io::Error> {
-        let db_instance = self.db_instance.as_ref().ok_or(std::io::Error::new(
-            std::io::ErrorKind::Other,
-            "No database opened",
-        ))?;
-        let mut iter = db_instance.raw_iterator();
-        let mut res: Vec<String> = Vec::new();
-        iter.seek(prefix);
-        while iter.valid() {
-            let key_u8 = iter.key().ok_or(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                "Found key of type None",
-            ))?;
-            let key_str: &str = std::str::from_utf8(key_u8)
-                .map_err(|error| std::io::Error::new(std::io::ErrorKind::Other, error))?;
-            if !key_str.starts_with(prefix) {
-                break;
+    fn list_namespaces(&mut self) -> Result<Vec<String>, FacadeError> {
+        let db = self.db_instance()?;
+        let mut res = Vec::new();
+        // Column families stay behind when all keys of a namespace are deleted.
+        for namespace in &self.namespaces {
+            if let Some(cf) = RocksDbFacade::column_family(db, namespace) {
+                let mut iter = db.raw_iterator_cf(cf);
+                iter.seek_to_first();
+                if iter.valid() {
+                    res.push(namespace.clone());
+                }
+                iter.status()?;
             }
-            res.push(key_str.to_string());
-            iter.next();
         }
         Ok(res)
     }
+
+    /// Estimates the usage from the properties of the column family of
+    /// `namespace` instead of reading all of its keys, so the numbers are
+    /// approximate: deleted and overwritten keys may still count until they are
+    /// compacted, and the size of the memtables includes their overhead.
+    fn namespace_usage(&mut self, namespace: &str) -> Result<NamespaceUsage, FacadeError> {
+        let db = self.db_instance()?;
+        let cf = match RocksDbFacade::column_family(db, namespace) {
+            Some(cf) => cf,
+            None => return Ok(NamespaceUsage::default()),
+        };
+        let keys = db
+            .property_int_value_cf(cf, properties::ESTIMATE_NUM_KEYS)?
+            .unwrap_or(0);
+        if keys == 0 {
+            return Ok(NamespaceUsage::default());
+        }
+        let live_data = db
+            .property_int_value_cf(cf, properties::ESTIMATE_LIVE_DATA_SIZE)?
+            .unwrap_or(0);
+        let memtables = db
+            .property_int_value_cf(cf, properties::CUR_SIZE_ALL_MEM_TABLES)?
+            .unwrap_or(0);
+        Ok(NamespaceUsage {
+            keys,
+            bytes: live_data + memtables,
+        })
+    }
+
+    fn drop_namespace(&mut self, namespace: &str) -> Result<(), FacadeError> {
+        let db = self.db_instance.as_mut().ok_or(FacadeError::NotOpen)?;
+        if self.namespaces.contains(namespace) {
+            db.drop_cf(&column_family_name(namespace))?;
+            self.namespaces.remove(namespace);
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
 // Unit tests go here
 mod tests {
+    use crate::backend::StorageBackend;
     use crate::facade::*;
 
     #[test]
@@ -128,16 +463,172 @@ mod tests {
         let mut rdb = RocksDbFacade::new();
         rdb.destroy_db("testpath").unwrap();
         rdb.open_db("testpath").unwrap();
-        rdb.write_db("cb", "cb").unwrap();
-        rdb.write_db("ab", "ab").unwrap();
-        rdb.write_db("a", "a").unwrap();
-        rdb.write_db("b", "b").unwrap();
-        rdb.write_db("ac", "ac").unwrap();
-        rdb.write_db("c", "c").unwrap();
-
-        let mut result = rdb.list_keys_with_prefix("a").unwrap();
+        rdb.write_db(b"cb", b"cb").unwrap();
+        rdb.write_db(b"ab", b"ab").unwrap();
+        rdb.write_db(b"a", b"a").unwrap();
+        rdb.write_db(b"b", b"b").unwrap();
+        rdb.write_db(b"ac", b"ac").unwrap();
+        rdb.write_db(b"c", b"c").unwrap();
+
+        let mut result = rdb.list_keys_with_prefix(b"a").unwrap();
         result.sort();
-        assert_eq!(result, vec!["a", "ab", "ac"]);
+        assert_eq!(result, vec![b"a".to_vec(), b"ab".to_vec(), b"ac".to_vec()]);
         rdb.destroy_db("testpath").unwrap();
     }
+
+    #[test]
+    fn read_db_errors_test() {
+        let mut rdb = RocksDbFacade::new();
+        rdb.destroy_db("testpath_errors").unwrap();
+
+        let result_closed = rdb.read_db(b"a");
+        rdb.open_db("testpath_errors").unwrap();
+        let result_missing = rdb.read_db(b"a");
+        rdb.write_db(b"a", b"a").unwrap();
+        let result_present = rdb.read_db(b"a");
+
+        assert!(matches!(result_closed, Err(FacadeError::NotOpen)));
+        assert!(matches!(result_missing, Err(FacadeError::NotFound)));
+        assert_eq!(result_present.unwrap(), b"a");
+        rdb.destroy_db("testpath_errors").unwrap();
+    }
+
+    #[test]
+    fn open_db_unreadable_column_families_test() {
+        let path = "testpath_unreadable";
+        std::fs::create_dir_all(path).unwrap();
+        std::fs::write(Path::new(path).join("CURRENT"), "MANIFEST-999999\n").unwrap();
+        let mut rdb = RocksDbFacade::new();
+
+        let result = rdb.open_db(path);
+
+        assert!(matches!(result, Err(FacadeError::Backend(_))));
+        std::fs::remove_dir_all(path).unwrap();
+    }
+
+    #[test]
+    fn binary_keys_and_values_test() {
+        let mut rdb = RocksDbFacade::new();
+        rdb.destroy_db("testpath_binary").unwrap();
+        rdb.open_db("testpath_binary").unwrap();
+        rdb.write_db(b"a.\xFF", b"\x00\xFF").unwrap();
+        rdb.write_db(b"a.b", b"b").unwrap();
+
+        let result_keys = rdb.list_keys_with_prefix(b"a.").unwrap();
+        let result_value = rdb.read_db(b"a.\xFF").unwrap();
+
+        assert_eq!(result_keys, vec![b"a.b".to_vec(), b"a.\xFF".to_vec()]);
+        assert_eq!(result_value, b"\x00\xFF");
+        rdb.destroy_db("testpath_binary").unwrap();
+    }
+
+    #[test]
+    fn write_batch_test() {
+        let mut rdb = RocksDbFacade::new();
+        rdb.destroy_db("testpath_batch").unwrap();
+        rdb.open_db("testpath_batch").unwrap();
+        rdb.write_db(b"a", b"a").unwrap();
+
+        rdb.write_batch(&[
+            BatchOperation::Put {
+                key: b"b".to_vec(),
+                value: b"b".to_vec(),
+            },
+            BatchOperation::Delete { key: b"a".to_vec() },
+            BatchOperation::Put {
+                key: b"b".to_vec(),
+                value: b"c".to_vec(),
+            },
+        ])
+        .unwrap();
+
+        assert!(matches!(rdb.read_db(b"a"), Err(FacadeError::NotFound)));
+        assert_eq!(rdb.read_db(b"b").unwrap(), b"c");
+        rdb.destroy_db("testpath_batch").unwrap();
+    }
+
+    #[test]
+    fn namespace_column_families_test() {
+        let mut rdb = RocksDbFacade::new();
+        rdb.destroy_db("testpath_namespaces").unwrap();
+        rdb.open_db("testpath_namespaces").unwrap();
+        rdb.write_db(&keys::namespace_key("a", b"x"), b"1").unwrap();
+        rdb.write_db(&keys::namespace_key("a_.b", b"y"), b"2")
+            .unwrap();
+        rdb.write_db(b"plain", b"3").unwrap();
+        // Written to the default column family like before namespaces had
+        // column families of their own.
+        let db = rdb.db_instance().unwrap();
+        db.put(keys::namespace_key("c", b"z"), b"4").unwrap();
+        db.delete(keys::COLUMN_FAMILY_LAYOUT_KEY).unwrap();
+        rdb.close_db().unwrap();
+
+        let result_column_families = DB::list_cf(&Options::default(), "testpath_namespaces");
+        rdb.open_db("testpath_namespaces").unwrap();
+        let result_namespace = rdb.list_keys_with_prefix(&keys::namespace_prefix("a"));
+        let result_all = rdb.list_all_keys();
+        let result_moved = rdb.read_db(&keys::namespace_key("c", b"z"));
+        // Not moved, since the layout is recorded by now.
+        rdb.db_instance()
+            .unwrap()
+            .put(keys::namespace_key("d", b"w"), b"5")
+            .unwrap();
+        rdb.close_db().unwrap();
+        rdb.open_db("testpath_namespaces").unwrap();
+        let result_not_moved = rdb.read_db(&keys::namespace_key("d", b"w"));
+
+        let mut column_families = result_column_families.unwrap();
+        column_families.sort();
+        assert_eq!(
+            column_families,
+            vec!["default", "namespace:a", "namespace:a_.b"]
+        );
+        assert_eq!(
+            result_namespace.unwrap(),
+            vec![keys::namespace_key("a", b"x")]
+        );
+        // Including the layout key.
+        assert_eq!(result_all.unwrap().len(), 5);
+        assert_eq!(result_moved.unwrap(), b"4");
+        assert!(matches!(result_not_moved, Err(FacadeError::NotFound)));
+        assert!(rdb.namespaces.contains("c"));
+        assert!(!rdb.namespaces.contains("d"));
+        assert!(rdb
+            .db_instance()
+            .unwrap()
+            .get(keys::namespace_key("c", b"z"))
+            .unwrap()
+            .is_none());
+        rdb.destroy_db("testpath_namespaces").unwrap();
+    }
+
+    #[test]
+    fn namespaces_test() {
+        let mut rdb = RocksDbFacade::new();
+        rdb.destroy_db("testpath_drop").unwrap();
+        rdb.open_db("testpath_drop").unwrap();
+        rdb.write_db(&keys::namespace_key("b", b"x"), b"12")
+            .unwrap();
+        rdb.write_db(&keys::namespace_key("b", b"yz"), b"3")
+            .unwrap();
+        rdb.write_db(&keys::namespace_key("a", b"x"), b"1").unwrap();
+        rdb.write_db(&keys::namespace_key("c", b"x"), b"1").unwrap();
+        rdb.delete_db(&keys::namespace_key("c", b"x")).unwrap();
+
+        let result_namespaces = rdb.list_namespaces().unwrap();
+        let result_usage = rdb.namespace_usage("b").unwrap();
+        rdb.drop_namespace("b").unwrap();
+        let result_dropped = rdb.read_db(&keys::namespace_key("b", b"x"));
+        rdb.write_db(&keys::namespace_key("b", b"x"), b"4").unwrap();
+
+        assert_eq!(result_namespaces, vec!["a", "b"]);
+        // Estimated, so only the number of keys is exact as long as nothing
+        // was overwritten or deleted.
+        assert_eq!(result_usage.keys, 2);
+        assert!(result_usage.bytes > 0);
+        assert!(matches!(result_dropped, Err(FacadeError::NotFound)));
+        assert_eq!(rdb.namespace_usage("b").unwrap().keys, 1);
+        assert_eq!(rdb.namespace_usage("d").unwrap(), NamespaceUsage::default());
+        rdb.destroy_db("testpath_drop").unwrap();
+    }
 }
@@ -0,0 +1,243 @@
+use crate::backend::{BatchOperation, StorageBackend};
+use crate::facade::FacadeError;
+
+/// Key under which the format of the namespaced keys is stored. A length
+/// prefix never starts with 0xFF and UTF-8 never contains it, so the key
+/// cannot clash with namespaced keys of either format.
+const KEY_FORMAT_KEY: &[u8] = b"\xFF\xFFkey_format";
+/// Key under which `RocksDbFacade` records that the namespaced keys were moved
+/// from the default column family to the column families of their namespaces.
+/// Like `KEY_FORMAT_KEY`, it cannot clash with namespaced keys.
+pub const COLUMN_FAMILY_LAYOUT_KEY: &[u8] = b"\xFF\xFFcolumn_families";
+/// Prefix of the keys above, which the service stores besides the namespaces.
+const META_KEY_PREFIX: &[u8] = b"\xFF\xFF";
+/// Namespaced keys as written by `namespace_key`. Format 1 is the legacy
+/// `{namespace}_.{key}`, which is ambiguous if a namespace or key contains `_.`.
+const KEY_FORMAT: u8 = 2;
+/// Prefix of the keys under which the last version given out in a namespace is
+/// stored (see `version_key`). Like `KEY_FORMAT_KEY`, it cannot clash with
+/// namespaced keys.
+const VERSION_KEY_PREFIX: &[u8] = b"\xFF\xFFversion:";
+/// Prefix of the keys that mark the namespaces which may hold keys that expire
+/// (see `expiry_marker_key`).
+pub const EXPIRY_MARKER_PREFIX: &[u8] = b"\xFF\xFFexpiring:";
+/// Prefix of the keys that mark the keys which got their defaults (see
+/// `seeded_marker_key`).
+const SEEDED_MARKER_PREFIX: &[u8] = b"\xFF\xFFseeded:";
+/// Separator between namespace and key in the legacy format.
+const LEGACY_SEPARATOR: &[u8] = b"_.";
+
+/// Encodes `key` in `namespace` as the length of the namespace (u32,
+/// big-endian), the namespace and the key.
+///
+/// The length tells where the namespace ends, so no two namespaces share a key
+/// and the keys of a namespace all start with its `namespace_prefix`.
+pub fn namespace_key(namespace: &str, key: &[u8]) -> Vec<u8> {
+    encode(namespace.as_bytes(), key)
+}
+
+/// Returns the prefix of all keys in `namespace`.
+pub fn namespace_prefix(namespace: &str) -> Vec<u8> {
+    encode(namespace.as_bytes(), b"")
+}
+
+/// Returns the key under which the last version given out in `namespace` is
+/// stored. It is not part of the namespace, so it outlives dropping it.
+pub fn version_key(namespace: &str) -> Vec<u8> {
+    [VERSION_KEY_PREFIX, namespace.as_bytes()].concat()
+}
+
+/// Returns the key whose presence tells that `namespace` may hold keys that
+/// expire, so that listing the keys of other namespaces need not read them.
+pub fn expiry_marker_key(namespace: &str) -> Vec<u8> {
+    [EXPIRY_MARKER_PREFIX, namespace.as_bytes()].concat()
+}
+
+/// Returns the namespace of a key written by `expiry_marker_key`, or `None` if
+/// `key` is not such a key.
+pub fn split_expiry_marker_key(key: &[u8]) -> Option<&str> {
+    std::str::from_utf8(key.strip_prefix(EXPIRY_MARKER_PREFIX)?).ok()
+}
+
+/// Returns the key whose presence tells that `key` in `namespace` got its
+/// default, so that it is not written again once the key was deleted.
+pub fn seeded_marker_key(namespace: &str, key: &[u8]) -> Vec<u8> {
+    [SEEDED_MARKER_PREFIX, &namespace_key(namespace, key)].concat()
+}
+
+/// Returns the prefix of all keys written by `seeded_marker_key` for `namespace`.
+pub fn seeded_marker_prefix(namespace: &str) -> Vec<u8> {
+    [SEEDED_MARKER_PREFIX, &namespace_prefix(namespace)].concat()
+}
+
+/// Returns the key of a key written by `seeded_marker_key`, or `None` if `key`
+/// is not such a key.
+pub fn split_seeded_marker_key(key: &[u8]) -> Option<&[u8]> {
+    Some(split_namespace_key(key.strip_prefix(SEEDED_MARKER_PREFIX)?)?.1)
+}
+
+/// Splits a key written by `namespace_key` into namespace and key, or returns
+/// `None` if `key` is not a namespaced key.
+pub fn split_namespace_key(key: &[u8]) -> Option<(&str, &[u8])> {
+    if key.len() < 4 {
+        return None;
+    }
+    let (len, rest) = key.split_at(4);
+    let len = u32::from_be_bytes(len.try_into().ok()?) as usize;
+    if rest.len() < len {
+        return None;
+    }
+    let (namespace, key) = rest.split_at(len);
+    Some((std::str::from_utf8(namespace).ok()?, key))
+}
+
+fn encode(namespace: &[u8], key: &[u8]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(4 + namespace.len() + key.len());
+    bytes.extend_from_slice(&(namespace.len() as u32).to_be_bytes());
+    bytes.extend_from_slice(namespace);
+    bytes.extend_from_slice(key);
+    bytes
+}
+
+/// Splits a legacy key into namespace and key at the first `_.`.
+///
+/// The legacy format cannot tell where a namespace containing `_.` ends, so
+/// such keys end up in the shorter namespace.
+fn split_legacy_key(key: &[u8]) -> Option<(&[u8], &[u8])> {
+    let position = key
+        .windows(LEGACY_SEPARATOR.len())
+        .position(|window| window == LEGACY_SEPARATOR)?;
+    Some((&key[..position], &key[position + LEGACY_SEPARATOR.len()..]))
+}
+
+/// Rewrites the keys of a database written with the legacy key format and
+/// records the current format, returning the number of rewritten keys.
+///
+/// All keys are rewritten in a single batch, so an interrupted migration is
+/// retried as a whole on the next open. Does nothing if the database already
+/// has the current format. Keys without a namespace separator were not written
+/// by the service and are left alone.
+pub fn migrate(backend: &mut dyn StorageBackend) -> Result<usize, FacadeError> {
+    match backend.read_db(KEY_FORMAT_KEY) {
+        Ok(format) if format == [KEY_FORMAT] => return Ok(0),
+        Ok(format) => {
+            return Err(FacadeError::Corruption(format!(
+                "Unknown key format {format:?}"
+            )))
+        }
+        Err(FacadeError::NotFound) => {}
+        Err(e) => return Err(e),
+    }
+
+    // Deletes go first, so that no rewritten key is deleted again if it
+    // happens to equal another legacy key.
+    let mut deletes = Vec::new();
+    let mut puts = Vec::new();
+    for key in backend.list_all_keys()? {
+        if key.starts_with(META_KEY_PREFIX) {
+            continue;
+        }
+        let new_key = match split_legacy_key(&key) {
+            Some((namespace, stripped_key)) => encode(namespace, stripped_key),
+            None => {
+                log::warn!(
+                    "Not migrating key without namespace: '{}'",
+                    String::from_utf8_lossy(&key)
+                );
+                continue;
+            }
+        };
+        let value = backend.read_db(&key)?;
+        deletes.push(BatchOperation::Delete { key });
+        puts.push(BatchOperation::Put {
+            key: new_key,
+            value,
+        });
+    }
+    let migrated = puts.len();
+    let mut operations = deletes;
+    operations.append(&mut puts);
+    operations.push(BatchOperation::Put {
+        key: KEY_FORMAT_KEY.to_vec(),
+        value: vec![KEY_FORMAT],
+    });
+    backend.write_batch(&operations)?;
+    Ok(migrated)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::keys::*;
+    use crate::memory::MemoryFacade;
+
+    #[test]
+    fn namespace_key_test() {
+        let nested_namespace = namespace_key("a_.b", b"c");
+        let nested_key = namespace_key("a", b"b_.c");
+
+        assert_ne!(nested_namespace, nested_key);
+        assert_eq!(nested_key, b"\0\0\0\x01ab_.c");
+        assert!(!nested_namespace.starts_with(&namespace_prefix("a")));
+        assert!(nested_key.starts_with(&namespace_prefix("a")));
+        assert_eq!(
+            split_namespace_key(&nested_namespace),
+            Some(("a_.b", b"c".as_slice()))
+        );
+        assert_eq!(
+            split_namespace_key(&namespace_prefix("")),
+            Some(("", b"".as_slice()))
+        );
+        assert_eq!(split_namespace_key(KEY_FORMAT_KEY), None);
+        assert_eq!(split_namespace_key(&version_key("a")), None);
+        assert_eq!(split_namespace_key(&expiry_marker_key("a")), None);
+        assert_eq!(split_namespace_key(&seeded_marker_key("a", b"b")), None);
+        assert!(seeded_marker_key("a", b"b").starts_with(&seeded_marker_prefix("a")));
+        assert!(!seeded_marker_key("ab", b"").starts_with(&seeded_marker_prefix("a")));
+        assert_eq!(
+            split_seeded_marker_key(&seeded_marker_key("a", b"b")),
+            Some(b"b".as_slice())
+        );
+        assert_eq!(split_seeded_marker_key(&namespace_key("a", b"b")), None);
+        assert_eq!(
+            split_expiry_marker_key(&expiry_marker_key("a_.b")),
+            Some("a_.b")
+        );
+        assert_eq!(split_expiry_marker_key(&version_key("a")), None);
+        assert_eq!(split_namespace_key(b"_.Vehicle.Speed"), None);
+    }
+
+    #[test]
+    fn migrate_test() {
+        let mut backend = MemoryFacade::new();
+        backend.open_db("").unwrap();
+        backend.write_db(b"_.Vehicle.Speed", b"10").unwrap();
+        backend.write_db(b"AppName_.Private.Info", b"x").unwrap();
+        backend.write_db(b"stray", b"y").unwrap();
+        backend.write_db(COLUMN_FAMILY_LAYOUT_KEY, b"").unwrap();
+
+        let result_first = migrate(&mut backend);
+        let result_second = migrate(&mut backend);
+
+        assert_eq!(result_first.unwrap(), 2);
+        assert_eq!(result_second.unwrap(), 0);
+        assert_eq!(
+            backend
+                .read_db(&namespace_key("", b"Vehicle.Speed"))
+                .unwrap(),
+            b"10"
+        );
+        assert_eq!(
+            backend
+                .read_db(&namespace_key("AppName", b"Private.Info"))
+                .unwrap(),
+            b"x"
+        );
+        assert_eq!(backend.read_db(b"stray").unwrap(), b"y");
+        assert!(matches!(
+            backend.read_db(b"_.Vehicle.Speed"),
+            Err(FacadeError::NotFound)
+        ));
+        assert_eq!(backend.read_db(COLUMN_FAMILY_LAYOUT_KEY).unwrap(), b"");
+        assert_eq!(backend.list_all_keys().unwrap().len(), 5);
+    }
+}
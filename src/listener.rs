@@ -0,0 +1,117 @@
+use std::fs::Permissions;
+use std::io::{Error, ErrorKind};
+use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+use tokio::net::UnixListener;
+use tokio_stream::wrappers::UnixListenerStream;
+
+use crate::config::UnixSocketConfig;
+
+/// Binds the Unix domain socket described by `config` and applies its permissions.
+///
+/// A socket file left behind by a previous run is removed first. Any other kind
+/// of file at the socket path is left untouched and reported as an error.
+pub fn bind_unix_socket(config: &UnixSocketConfig) -> Result<UnixListenerStream, Error> {
+    if let Some(parent) = config.path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    match std::fs::symlink_metadata(&config.path) {
+        Ok(metadata) if metadata.file_type().is_socket() => std::fs::remove_file(&config.path)?,
+        Ok(_) => {
+            return Err(Error::new(
+                ErrorKind::AlreadyExists,
+                format!("'{}' exists and is not a socket", config.path.display()),
+            ))
+        }
+        Err(e) if e.kind() == ErrorKind::NotFound => {}
+        Err(e) => return Err(e),
+    }
+    let listener = UnixListener::bind(&config.path)?;
+    std::fs::set_permissions(&config.path, Permissions::from_mode(config.mode))?;
+    Ok(UnixListenerStream::new(listener))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::memory::MemoryFacade;
+    use crate::server::DatabaseManager;
+    use crate::storage_api::database_client::DatabaseClient;
+    use crate::storage_api::database_server::DatabaseServer;
+    use crate::storage_api::{Key, KeyValue};
+    use tokio::net::UnixStream;
+    use tonic::transport::{Endpoint, Server, Uri};
+
+    #[tokio::test]
+    async fn test_serve_on_unix_socket() {
+        // Arrange
+        let socket = UnixSocketConfig {
+            path: std::env::temp_dir().join(format!("agl-storage-{}.sock", uuid::Uuid::new_v4())),
+            mode: 0o600,
+        };
+        let incoming = bind_unix_socket(&socket).unwrap();
+        let database_manager =
+            DatabaseManager::new(Box::new(MemoryFacade::new()), &Config::default());
+        let server = Server::builder().add_service(DatabaseServer::new(database_manager));
+        let server_task = tokio::spawn(server.serve_with_incoming(incoming));
+
+        let socket_path = socket.path.clone();
+        let channel = Endpoint::from_static("http://localhost")
+            .connect_with_connector(tower::service_fn(move |_: Uri| {
+                UnixStream::connect(socket_path.clone())
+            }))
+            .await
+            .unwrap();
+        let mut client = DatabaseClient::new(channel);
+
+        // Act
+        let response_write = client
+            .write(KeyValue {
+                key: "Vehicle.Infotainment.Radio.Volume".to_string(),
+                value: "12".to_string(),
+                namespace: "".to_string(),
+            })
+            .await
+            .unwrap();
+        let response_read = client
+            .read(Key {
+                key: "Vehicle.Infotainment.Radio.Volume".to_string(),
+                namespace: "".to_string(),
+            })
+            .await
+            .unwrap();
+        let mode = std::fs::metadata(&socket.path)
+            .unwrap()
+            .permissions()
+            .mode();
+
+        // Assert
+        assert!(response_write.into_inner().success);
+        assert_eq!(response_read.into_inner().result, "12");
+        assert_eq!(mode & 0o777, 0o600);
+
+        // Clean up.
+        server_task.abort();
+        std::fs::remove_file(&socket.path).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_bind_does_not_remove_regular_file() {
+        // Arrange
+        let socket = UnixSocketConfig {
+            path: std::env::temp_dir().join(format!("agl-storage-{}.sock", uuid::Uuid::new_v4())),
+            mode: 0o600,
+        };
+        std::fs::write(&socket.path, "data").unwrap();
+
+        // Act
+        let result = bind_unix_socket(&socket);
+
+        // Assert
+        assert!(result.is_err());
+        assert_eq!(std::fs::read_to_string(&socket.path).unwrap(), "data");
+
+        // Clean up.
+        std::fs::remove_file(&socket.path).unwrap();
+    }
+}
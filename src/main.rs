@@ -1,12 +1,35 @@
+use clap::Parser;
+use futures::future::BoxFuture;
+use std::time::Duration;
+use tonic::transport::server::Router;
 use tonic::transport::Server;
 
+use auth::TokenInterceptor;
+use cli::Args;
+use config::Config;
 use server::DatabaseManager;
 use storage_api::database_server::DatabaseServer;
+use storage_api_v2::database_server::DatabaseServer as DatabaseServerV2;
 
+pub mod access;
+pub mod auth;
+pub mod backend;
+pub mod cli;
+pub mod config;
+pub mod defaults;
 pub mod facade;
+pub mod keys;
+pub mod listener;
+pub mod memory;
+pub mod record;
 pub mod server;
+pub mod server_v2;
 pub mod service;
 pub mod storage_api;
+#[path = "storage_api.v2.rs"]
+pub mod storage_api_v2;
+pub mod tls;
+pub mod tree;
 
 mod storage_api_proto {
     include!("storage_api.rs");
@@ -17,19 +40,109 @@ mod storage_api_proto {
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    // API port is defined here
-    let addr = "127.0.0.1:50054".parse()?;
-    let dbmanager = DatabaseManager::new();
-
-    let reflection_service = tonic_reflection::server::Builder::configure()
-        .register_encoded_file_descriptor_set(storage_api_proto::FILE_DESCRIPTOR_SET)
-        .build()
-        .unwrap();
-
-    Server::builder()
-        .add_service(DatabaseServer::new(dbmanager))
-        .add_service(reflection_service)
-        .serve(addr)
-        .await?;
+    let args = Args::parse();
+    let config = match Config::load(&args) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{e}");
+            std::process::exit(1);
+        }
+    };
+    env_logger::Builder::new()
+        .parse_filters(&config.logging.level)
+        .init();
+
+    log::info!(
+        "Using {:?} backend with database at '{}'",
+        config.database.backend,
+        config.database.path
+    );
+    let backend = config.database.backend.create(&config.database.rocksdb);
+    let dbmanager = DatabaseManager::new(backend, &config);
+    if let Some(file) = &config.defaults.file {
+        let defaults = defaults::load(file)?;
+        let mut db_service = dbmanager.db_service.lock().await;
+        for namespace in &config.defaults.namespaces {
+            match db_service.seed_defaults(&defaults, namespace) {
+                Ok(None) => log::info!(
+                    "Skipping read-only namespace '{namespace}', see defaults.write_read_only"
+                ),
+                Ok(Some(seeded)) => log::info!(
+                    "Wrote {} of {} defaults to namespace '{}'",
+                    seeded.len(),
+                    defaults.len(),
+                    namespace
+                ),
+                Err(e) => log::error!("Unable to write defaults to namespace '{namespace}': {e}"),
+            }
+        }
+    }
+    if config.database.expiry_sweep_interval_secs > 0 {
+        dbmanager.spawn_expiry_sweeper(Duration::from_secs(
+            config.database.expiry_sweep_interval_secs,
+        ));
+    }
+
+    let mut servers: Vec<BoxFuture<Result<(), tonic::transport::Error>>> = Vec::new();
+    if config.server.tcp {
+        if config.access.enabled
+            && !config.auth.enabled
+            && config
+                .server
+                .tls
+                .as_ref()
+                .is_none_or(|tls| tls.client_ca.is_none())
+        {
+            log::warn!(
+                "Access control is enabled, but TCP clients can authenticate with neither \
+                 a client certificate nor a token, so all of them are denied"
+            );
+        }
+        let mut server = Server::builder();
+        match &config.server.tls {
+            Some(tls) => {
+                log::info!("Listening on {} with TLS", config.server.listen);
+                server = tls::configure_server(server, tls)?;
+            }
+            None => log::info!("Listening on {}", config.server.listen),
+        }
+        let router = build_router(server, dbmanager.clone(), &config);
+        servers.push(Box::pin(router.serve(config.server.listen)));
+    }
+    if let Some(socket) = &config.server.unix_socket {
+        log::info!("Listening on Unix socket '{}'", socket.path.display());
+        let incoming = listener::bind_unix_socket(socket)?;
+        let router = build_router(Server::builder(), dbmanager.clone(), &config);
+        servers.push(Box::pin(router.serve_with_incoming(incoming)));
+    }
+    if servers.is_empty() {
+        return Err("Neither TCP nor a Unix socket is configured to listen on".into());
+    }
+
+    futures::future::try_join_all(servers).await?;
     Ok(())
 }
+
+fn build_router(mut server: Server, dbmanager: DatabaseManager, config: &Config) -> Router {
+    let reflection_service = if config.server.reflection {
+        Some(
+            tonic_reflection::server::Builder::configure()
+                .register_encoded_file_descriptor_set(storage_api_proto::FILE_DESCRIPTOR_SET)
+                .build()
+                .unwrap(),
+        )
+    } else {
+        None
+    };
+
+    server
+        .add_service(DatabaseServer::with_interceptor(
+            dbmanager.clone(),
+            TokenInterceptor::new(&config.auth, &config.access),
+        ))
+        .add_service(DatabaseServerV2::with_interceptor(
+            dbmanager,
+            TokenInterceptor::new(&config.auth, &config.access),
+        ))
+        .add_optional_service(reflection_service)
+}
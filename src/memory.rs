@@ -0,0 +1,178 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::backend::{BatchOperation, NamespaceUsage, StorageBackend};
+use crate::facade::FacadeError;
+use crate::keys;
+
+/// Storage backend that keeps all data in an ordered map in memory.
+///
+/// Nothing is written to disk, so the data is lost when the server stops. This
+/// is meant for tests and for volatile deployments such as a guest mode.
+#[derive(Debug, Default)]
+pub struct MemoryFacade {
+    is_open: bool,
+    entries: BTreeMap<Vec<u8>, Vec<u8>>,
+}
+
+impl MemoryFacade {
+    pub fn new() -> MemoryFacade {
+        MemoryFacade::default()
+    }
+
+    fn entries(&mut self) -> Result<&mut BTreeMap<Vec<u8>, Vec<u8>>, FacadeError> {
+        if !self.is_open {
+            return Err(FacadeError::NotOpen);
+        }
+        Ok(&mut self.entries)
+    }
+}
+
+impl StorageBackend for MemoryFacade {
+    fn open_db(&mut self, _path: &str) -> Result<(), FacadeError> {
+        self.is_open = true;
+        Ok(())
+    }
+
+    fn close_db(&mut self) -> Result<(), FacadeError> {
+        self.is_open = false;
+        Ok(())
+    }
+
+    fn destroy_db(&mut self, _path: &str) -> Result<(), FacadeError> {
+        self.close_db()?;
+        self.entries.clear();
+        Ok(())
+    }
+
+    fn write_db(&mut self, key: &[u8], value: &[u8]) -> Result<(), FacadeError> {
+        self.entries()?.insert(key.to_vec(), value.to_vec());
+        Ok(())
+    }
+
+    fn read_db(&mut self, key: &[u8]) -> Result<Vec<u8>, FacadeError> {
+        self.entries()?
+            .get(key)
+            .cloned()
+            .ok_or(FacadeError::NotFound)
+    }
+
+    fn delete_db(&mut self, key: &[u8]) -> Result<(), FacadeError> {
+        self.entries()?.remove(key);
+        Ok(())
+    }
+
+    fn write_batch(&mut self, operations: &[BatchOperation]) -> Result<(), FacadeError> {
+        // Nothing can fail once the database is open, so the batch is atomic.
+        let entries = self.entries()?;
+        for operation in operations {
+            match operation {
+                BatchOperation::Put { key, value } => {
+                    entries.insert(key.clone(), value.clone());
+                }
+                BatchOperation::Delete { key } => {
+                    entries.remove(key);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn list_all_keys(&mut self) -> Result<Vec<Vec<u8>>, FacadeError> {
+        Ok(self.entries()?.keys().cloned().collect())
+    }
+
+    fn list_keys_with_prefix(&mut self, prefix: &[u8]) -> Result<Vec<Vec<u8>>, FacadeError> {
+        Ok(self
+            .entries()?
+            .range(prefix.to_vec()..)
+            .map(|(key, _)| key)
+            .take_while(|key| key.starts_with(prefix))
+            .cloned()
+            .collect())
+    }
+
+    fn list_namespaces(&mut self) -> Result<Vec<String>, FacadeError> {
+        let namespaces: BTreeSet<&str> = self
+            .entries()?
+            .keys()
+            .filter_map(|key| keys::split_namespace_key(key))
+            .map(|(namespace, _)| namespace)
+            .collect();
+        Ok(namespaces.into_iter().map(str::to_string).collect())
+    }
+
+    fn namespace_usage(&mut self, namespace: &str) -> Result<NamespaceUsage, FacadeError> {
+        let prefix = keys::namespace_prefix(namespace);
+        let mut usage = NamespaceUsage::default();
+        for (key, value) in self
+            .entries()?
+            .range(prefix.clone()..)
+            .take_while(|(key, _)| key.starts_with(&prefix))
+        {
+            usage.keys += 1;
+            usage.bytes += (key.len() - prefix.len() + value.len()) as u64;
+        }
+        Ok(usage)
+    }
+
+    fn drop_namespace(&mut self, namespace: &str) -> Result<(), FacadeError> {
+        let prefix = keys::namespace_prefix(namespace);
+        self.entries()?.retain(|key, _| !key.starts_with(&prefix));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::backend::StorageBackend;
+    use crate::memory::*;
+
+    #[test]
+    fn list_keys_with_prefix_test() {
+        let mut mdb = MemoryFacade::new();
+        mdb.open_db("unused").unwrap();
+        mdb.write_db(b"cb", b"cb").unwrap();
+        mdb.write_db(b"ab", b"ab").unwrap();
+        mdb.write_db(b"a", b"a").unwrap();
+        mdb.write_db(b"b", b"b").unwrap();
+        mdb.write_db(b"ac", b"ac").unwrap();
+        mdb.write_db(b"c", b"c").unwrap();
+
+        let result = mdb.list_keys_with_prefix(b"a").unwrap();
+        assert_eq!(result, vec![b"a".to_vec(), b"ab".to_vec(), b"ac".to_vec()]);
+    }
+
+    #[test]
+    fn destroy_db_test() {
+        let mut mdb = MemoryFacade::new();
+        mdb.open_db("unused").unwrap();
+        mdb.write_db(b"a", b"a").unwrap();
+
+        mdb.destroy_db("unused").unwrap();
+
+        assert!(mdb.read_db(b"a").is_err());
+        mdb.open_db("unused").unwrap();
+        assert!(mdb.list_all_keys().unwrap().is_empty());
+    }
+
+    #[test]
+    fn namespaces_test() {
+        let mut mdb = MemoryFacade::new();
+        mdb.open_db("unused").unwrap();
+        mdb.write_db(&keys::namespace_key("b", b"x"), b"12")
+            .unwrap();
+        mdb.write_db(&keys::namespace_key("b", b"yz"), b"3")
+            .unwrap();
+        mdb.write_db(&keys::namespace_key("a", b"x"), b"1").unwrap();
+        mdb.write_db(b"plain", b"1").unwrap();
+
+        let result_namespaces = mdb.list_namespaces().unwrap();
+        let result_usage = mdb.namespace_usage("b").unwrap();
+        mdb.drop_namespace("b").unwrap();
+
+        assert_eq!(result_namespaces, vec!["a", "b"]);
+        assert_eq!(result_usage, NamespaceUsage { keys: 2, bytes: 6 });
+        assert_eq!(mdb.list_namespaces().unwrap(), vec!["a"]);
+        assert_eq!(mdb.list_all_keys().unwrap().len(), 2);
+    }
+}
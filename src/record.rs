@@ -0,0 +1,463 @@
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::facade::FacadeError;
+
+/// First byte of an encoded record. It never starts valid UTF-8, so values
+/// written as plain strings before records existed can be told apart.
+const RECORD_MARKER: u8 = 0xFF;
+/// Record encoding with a UTF-8 string value.
+const FORMAT_STRING: u8 = 1;
+/// Record encoding with a type tag in front of the value.
+const FORMAT_TYPED: u8 = 2;
+/// Record encoding with an expiry time between header and typed value.
+const FORMAT_EXPIRING: u8 = 3;
+/// Length of marker, format, version and modification time.
+const HEADER_LEN: usize = 2 + 8 + 8;
+
+/// Type of a `Value`. The discriminant tags the type in the record encoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueType {
+    String = 0,
+    Int = 1,
+    Double = 2,
+    Bool = 3,
+    Bytes = 4,
+    StringList = 5,
+}
+
+impl ValueType {
+    const ALL: [ValueType; 6] = [
+        ValueType::String,
+        ValueType::Int,
+        ValueType::Double,
+        ValueType::Bool,
+        ValueType::Bytes,
+        ValueType::StringList,
+    ];
+
+    fn tag(self) -> u8 {
+        self as u8
+    }
+
+    fn from_tag(tag: u8) -> Option<ValueType> {
+        ValueType::ALL
+            .into_iter()
+            .find(|value_type| value_type.tag() == tag)
+    }
+}
+
+impl fmt::Display for ValueType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ValueType::String => "string",
+            ValueType::Int => "int",
+            ValueType::Double => "double",
+            ValueType::Bool => "bool",
+            ValueType::Bytes => "bytes",
+            ValueType::StringList => "string list",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// A value stored under a key, which keeps the type it was written with.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    String(String),
+    Int(i64),
+    Double(f64),
+    Bool(bool),
+    Bytes(Vec<u8>),
+    StringList(Vec<String>),
+}
+
+impl Value {
+    pub fn value_type(&self) -> ValueType {
+        match self {
+            Value::String(_) => ValueType::String,
+            Value::Int(_) => ValueType::Int,
+            Value::Double(_) => ValueType::Double,
+            Value::Bool(_) => ValueType::Bool,
+            Value::Bytes(_) => ValueType::Bytes,
+            Value::StringList(_) => ValueType::StringList,
+        }
+    }
+
+    /// Size of the value in bytes as clients send it: the bytes of a string or
+    /// bytes value, 8 for ints and doubles, 1 for bools and the bytes of all
+    /// strings of a list. Quotas count this size rather than that of the
+    /// record, so they do not depend on how values are stored.
+    pub fn size(&self) -> u64 {
+        match self {
+            Value::String(value) => value.len() as u64,
+            Value::Int(_) | Value::Double(_) => 8,
+            Value::Bool(_) => 1,
+            Value::Bytes(value) => value.len() as u64,
+            Value::StringList(values) => values.iter().map(|value| value.len() as u64).sum(),
+        }
+    }
+
+    /// Returns the value as text for the string-based APIs, or `None` for bytes
+    /// and string lists, which have no unambiguous text form.
+    pub fn as_text(&self) -> Option<String> {
+        match self {
+            Value::String(value) => Some(value.clone()),
+            Value::Int(value) => Some(value.to_string()),
+            Value::Double(value) => Some(value.to_string()),
+            Value::Bool(value) => Some(value.to_string()),
+            Value::Bytes(_) | Value::StringList(_) => None,
+        }
+    }
+
+    fn encode_into(&self, bytes: &mut Vec<u8>) {
+        bytes.push(self.value_type().tag());
+        match self {
+            Value::String(value) => bytes.extend_from_slice(value.as_bytes()),
+            Value::Int(value) => bytes.extend_from_slice(&value.to_be_bytes()),
+            Value::Double(value) => bytes.extend_from_slice(&value.to_bits().to_be_bytes()),
+            Value::Bool(value) => bytes.push(u8::from(*value)),
+            Value::Bytes(value) => bytes.extend_from_slice(value),
+            Value::StringList(values) => {
+                for value in values {
+                    bytes.extend_from_slice(&(value.len() as u32).to_be_bytes());
+                    bytes.extend_from_slice(value.as_bytes());
+                }
+            }
+        }
+    }
+
+    fn decode(bytes: &[u8]) -> Result<Value, FacadeError> {
+        let (&tag, payload) = bytes
+            .split_first()
+            .ok_or_else(|| FacadeError::Corruption(String::from("Record without value type")))?;
+        let value_type = ValueType::from_tag(tag)
+            .ok_or_else(|| FacadeError::Corruption(format!("Unknown value type {tag}")))?;
+        let fixed = |len: usize| -> Result<&[u8], FacadeError> {
+            if payload.len() != len {
+                return Err(FacadeError::Corruption(format!(
+                    "Value of type {} has {} instead of {} bytes",
+                    value_type,
+                    payload.len(),
+                    len
+                )));
+            }
+            Ok(payload)
+        };
+        Ok(match value_type {
+            ValueType::String => Value::String(std::str::from_utf8(payload)?.to_string()),
+            ValueType::Int => Value::Int(i64::from_be_bytes(be_bytes(fixed(8)?))),
+            ValueType::Double => {
+                Value::Double(f64::from_bits(u64::from_be_bytes(be_bytes(fixed(8)?))))
+            }
+            ValueType::Bool => Value::Bool(fixed(1)?[0] != 0),
+            ValueType::Bytes => Value::Bytes(payload.to_vec()),
+            ValueType::StringList => {
+                let mut values = Vec::new();
+                let mut rest = payload;
+                while !rest.is_empty() {
+                    if rest.len() < 4 {
+                        return Err(FacadeError::Corruption(String::from(
+                            "Truncated length in string list",
+                        )));
+                    }
+                    let (len, tail) = rest.split_at(4);
+                    let len = u32::from_be_bytes(be_bytes(len)) as usize;
+                    if tail.len() < len {
+                        return Err(FacadeError::Corruption(String::from(
+                            "Truncated string in string list",
+                        )));
+                    }
+                    let (value, tail) = tail.split_at(len);
+                    values.push(std::str::from_utf8(value)?.to_string());
+                    rest = tail;
+                }
+                Value::StringList(values)
+            }
+        })
+    }
+}
+
+impl From<&str> for Value {
+    fn from(value: &str) -> Value {
+        Value::String(value.to_string())
+    }
+}
+
+/// Shown in messages; bytes are summarized by their length.
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Bytes(value) => write!(f, "<{} bytes>", value.len()),
+            Value::StringList(values) => write!(f, "[{}]", values.join(", ")),
+            _ => write!(f, "{}", self.as_text().unwrap_or_default()),
+        }
+    }
+}
+
+/// A value together with the metadata stored alongside it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Record {
+    pub value: Value,
+    /// Number of writes of the key since it was created, starting at 1. Values
+    /// written before versioning was introduced have version 0.
+    pub version: u64,
+    /// Time of the last write in milliseconds since the Unix epoch; 0 if unknown.
+    pub modified_ms: u64,
+    /// Time in milliseconds since the Unix epoch from which on the key counts
+    /// as deleted, or `None` if it does not expire.
+    pub expires_ms: Option<u64>,
+}
+
+impl Record {
+    /// Creates a record with the given version, modified now.
+    pub fn new(value: Value, version: u64) -> Record {
+        Record {
+            value,
+            version,
+            modified_ms: now_ms(),
+            expires_ms: None,
+        }
+    }
+
+    /// Makes the record expire `ttl_ms` milliseconds after its modification.
+    pub fn with_ttl(mut self, ttl_ms: u64) -> Record {
+        self.expires_ms = Some(self.modified_ms.saturating_add(ttl_ms));
+        self
+    }
+
+    /// Returns whether the record has expired at `now_ms`.
+    pub fn is_expired(&self, now_ms: u64) -> bool {
+        matches!(self.expires_ms, Some(expires_ms) if expires_ms <= now_ms)
+    }
+
+    /// Encodes the record as: marker, format, version and modification time
+    /// (both big-endian), the expiry time (big-endian) if the record expires,
+    /// followed by the type tag and the value.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(HEADER_LEN + 8 + 1 + 8);
+        bytes.push(RECORD_MARKER);
+        bytes.push(match self.expires_ms {
+            Some(_) => FORMAT_EXPIRING,
+            None => FORMAT_TYPED,
+        });
+        bytes.extend_from_slice(&self.version.to_be_bytes());
+        bytes.extend_from_slice(&self.modified_ms.to_be_bytes());
+        if let Some(expires_ms) = self.expires_ms {
+            bytes.extend_from_slice(&expires_ms.to_be_bytes());
+        }
+        self.value.encode_into(&mut bytes);
+        bytes
+    }
+
+    /// Decodes a record written by `encode`, an untyped record with a string
+    /// value, or a plain value written before records existed, which gets
+    /// version 0 and is a string if it is valid UTF-8 and bytes otherwise.
+    pub fn decode(bytes: &[u8]) -> Result<Record, FacadeError> {
+        if bytes.first() != Some(&RECORD_MARKER) {
+            let value = match std::str::from_utf8(bytes) {
+                Ok(value) => Value::String(value.to_string()),
+                Err(_) => Value::Bytes(bytes.to_vec()),
+            };
+            return Ok(Record {
+                value,
+                version: 0,
+                modified_ms: 0,
+                expires_ms: None,
+            });
+        }
+        if bytes.len() < HEADER_LEN {
+            return Err(FacadeError::Corruption(format!(
+                "Record of {} bytes is shorter than its header",
+                bytes.len()
+            )));
+        }
+        let (version, rest) = bytes[2..].split_at(8);
+        let (modified_ms, value) = rest.split_at(8);
+        let mut expires_ms = None;
+        let value = match bytes[1] {
+            FORMAT_STRING => Value::String(std::str::from_utf8(value)?.to_string()),
+            FORMAT_TYPED => Value::decode(value)?,
+            FORMAT_EXPIRING => {
+                if value.len() < 8 {
+                    return Err(FacadeError::Corruption(String::from(
+                        "Record without expiry time",
+                    )));
+                }
+                let (expiry, value) = value.split_at(8);
+                expires_ms = Some(u64::from_be_bytes(be_bytes(expiry)));
+                Value::decode(value)?
+            }
+            format => {
+                return Err(FacadeError::Corruption(format!(
+                    "Unknown record format {format}"
+                )))
+            }
+        };
+        Ok(Record {
+            value,
+            version: u64::from_be_bytes(be_bytes(version)),
+            modified_ms: u64::from_be_bytes(be_bytes(modified_ms)),
+            expires_ms,
+        })
+    }
+}
+
+/// Returns the current time in milliseconds since the Unix epoch.
+pub fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+/// Converts a slice whose length was checked into an array.
+fn be_bytes<const N: usize>(bytes: &[u8]) -> [u8; N] {
+    bytes.try_into().expect("slice of checked length")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::record::*;
+
+    #[test]
+    fn encode_decode_test() {
+        for value in [
+            Value::String("Ä12".to_string()),
+            Value::Int(-12),
+            Value::Double(21.5),
+            Value::Bool(true),
+            Value::Bytes(vec![0xFF, 0, 1]),
+            Value::StringList(vec![
+                "1live".to_string(),
+                String::new(),
+                "WDR 2".to_string(),
+            ]),
+        ] {
+            let record = Record {
+                value,
+                version: 3,
+                modified_ms: 1_700_000_000_000,
+                expires_ms: None,
+            };
+            let expiring = record.clone().with_ttl(5_000);
+
+            let decoded = Record::decode(&record.encode());
+            let decoded_expiring = Record::decode(&expiring.encode());
+
+            assert_eq!(decoded.unwrap(), record);
+            assert_eq!(decoded_expiring.unwrap(), expiring);
+        }
+    }
+
+    #[test]
+    fn size_test() {
+        assert_eq!(Value::from("abc").size(), 3);
+        assert_eq!(Value::Int(-1).size(), 8);
+        assert_eq!(Value::Double(0.5).size(), 8);
+        assert_eq!(Value::Bool(true).size(), 1);
+        assert_eq!(Value::Bytes(vec![0; 5]).size(), 5);
+        assert_eq!(
+            Value::StringList(vec!["ab".to_string(), String::new()]).size(),
+            2
+        );
+    }
+
+    #[test]
+    fn expiry_test() {
+        let record = Record {
+            value: Value::Int(1),
+            version: 1,
+            modified_ms: 1_000,
+            expires_ms: None,
+        };
+        let expiring = record.clone().with_ttl(500);
+
+        assert_eq!(expiring.expires_ms, Some(1_500));
+        assert!(!record.is_expired(u64::MAX));
+        assert!(!expiring.is_expired(1_499));
+        assert!(expiring.is_expired(1_500));
+        assert!(matches!(
+            Record::decode(&[
+                RECORD_MARKER,
+                FORMAT_EXPIRING,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                1,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                0,
+                1,
+                0
+            ]),
+            Err(FacadeError::Corruption(_))
+        ));
+    }
+
+    #[test]
+    fn decode_legacy_and_damaged_test() {
+        let mut untyped = vec![RECORD_MARKER, FORMAT_STRING];
+        untyped.extend_from_slice(&[0, 0, 0, 0, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 9]);
+        untyped.extend_from_slice(b"21");
+        let mut truncated_int = untyped.clone();
+        truncated_int[1] = FORMAT_TYPED;
+        truncated_int[HEADER_LEN] = ValueType::Int.tag();
+
+        let result_legacy = Record::decode(b"12");
+        let result_legacy_binary = Record::decode(b"1\xC3");
+        let result_empty = Record::decode(b"");
+        let result_untyped = Record::decode(&untyped);
+        let result_truncated = Record::decode(&[RECORD_MARKER, FORMAT_TYPED, 0]);
+        let result_unknown_format = Record::decode(&[RECORD_MARKER; HEADER_LEN]);
+        let result_truncated_int = Record::decode(&truncated_int);
+
+        assert_eq!(
+            result_legacy.unwrap(),
+            Record {
+                value: Value::String("12".to_string()),
+                version: 0,
+                modified_ms: 0,
+                expires_ms: None,
+            }
+        );
+        assert_eq!(
+            result_legacy_binary.unwrap().value,
+            Value::Bytes(b"1\xC3".to_vec())
+        );
+        assert_eq!(result_empty.unwrap().value, Value::String(String::new()));
+        assert_eq!(
+            result_untyped.unwrap(),
+            Record {
+                value: Value::String("21".to_string()),
+                version: 2,
+                modified_ms: 9,
+                expires_ms: None,
+            }
+        );
+        assert!(matches!(result_truncated, Err(FacadeError::Corruption(_))));
+        assert!(matches!(
+            result_unknown_format,
+            Err(FacadeError::Corruption(_))
+        ));
+        assert!(matches!(
+            result_truncated_int,
+            Err(FacadeError::Corruption(_))
+        ));
+    }
+
+    #[test]
+    fn as_text_test() {
+        assert_eq!(Value::Int(12).as_text().unwrap(), "12");
+        assert_eq!(Value::Bool(false).as_text().unwrap(), "false");
+        assert_eq!(Value::Bytes(vec![1]).as_text(), None);
+    }
+}
@@ -1,5 +1,8 @@
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+use tokio::time::MissedTickBehavior;
 use tonic::{Request, Response, Status};
 
 use crate::storage_api::database_server::Database;
@@ -7,28 +10,85 @@ use crate::storage_api::{
     DestroyArguments, Key, KeyValue, ListResponse, ReadResponse, StandardResponse, SubtreeInfo,
 };
 
-use crate::service::DbService;
+use crate::access::{authorize, Identity, Operation};
+use crate::backend::StorageBackend;
+use crate::config::{AccessConfig, Config};
+use crate::service::{DbService, ServiceError};
 
-#[derive(Debug)]
+/// Number of keys the expiry sweeper checks while holding the service.
+const EXPIRY_SWEEP_CHUNK: usize = 1000;
+
+#[derive(Debug, Clone)]
 pub struct DatabaseManager {
-    db_service: Arc<Mutex<DbService>>,
+    pub(crate) db_service: Arc<Mutex<DbService>>,
+    pub(crate) access: Arc<AccessConfig>,
 }
 
 impl DatabaseManager {
-    pub fn new() -> DatabaseManager {
+    /// Creates a manager whose service stores its data in `backend`, configured by `config`.
+    pub fn new(backend: Box<dyn StorageBackend>, config: &Config) -> DatabaseManager {
         DatabaseManager {
-            db_service: Arc::new(Mutex::new(DbService::new())),
+            db_service: Arc::new(Mutex::new(DbService::new(backend, config))),
+            access: Arc::new(config.access.clone()),
+        }
+    }
+
+    /// Removes the expired keys from the database now and then every `period`,
+    /// see `DbService::expiry_scans`.
+    pub fn spawn_expiry_sweeper(&self, period: Duration) -> JoinHandle<()> {
+        let db_service = self.db_service.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            loop {
+                interval.tick().await;
+                match remove_expired(&db_service).await {
+                    Ok(0) => {}
+                    Ok(removed) => log::info!("Removed {removed} expired keys"),
+                    Err(e) => log::warn!("{e}"),
+                }
+            }
+        })
+    }
+}
+
+/// Removes the expired keys in chunks of `EXPIRY_SWEEP_CHUNK` keys, releasing
+/// the service between them so that requests are not held up by the sweep.
+async fn remove_expired(db_service: &Mutex<DbService>) -> Result<usize, ServiceError> {
+    let scans = db_service.lock().await.expiry_scans()?;
+    let mut removed = 0;
+    for mut scan in scans {
+        while !scan.is_done() {
+            removed += db_service
+                .lock()
+                .await
+                .remove_expired_keys(&mut scan, EXPIRY_SWEEP_CHUNK)?;
         }
+        db_service.lock().await.finish_expiry_scan(scan)?;
     }
+    Ok(removed)
 }
 
 #[tonic::async_trait]
 impl Database for DatabaseManager {
     async fn destroy_db(
         &self,
-        _request: Request<DestroyArguments>,
+        request: Request<DestroyArguments>,
     ) -> Result<Response<StandardResponse>, Status> {
-        let res: (bool, String) = self.db_service.lock().await.destroy_db();
+        authorize(
+            &self.access,
+            &Identity::of(&request),
+            None,
+            Operation::DestroyDb,
+        )?;
+        let mut db_service = self.db_service.lock().await;
+        let res = match db_service.destroy_db() {
+            Ok(()) => (
+                true,
+                String::from("Destroyed database at path '") + db_service.db_path() + "'",
+            ),
+            Err(e) => (false, e.to_string()),
+        };
 
         Ok(Response::new(StandardResponse {
             success: res.0,
@@ -40,11 +100,30 @@ impl Database for DatabaseManager {
         &self,
         request: Request<KeyValue>,
     ) -> Result<Response<StandardResponse>, Status> {
+        let identity = Identity::of(&request);
         let keyvalue = request.into_inner();
-        let res: (bool, String) = self.db_service.lock().await.write_db(
-            &keyvalue.key,
-            &keyvalue.value,
-            &keyvalue.namespace,
+        authorize(
+            &self.access,
+            &identity,
+            Some(&keyvalue.namespace),
+            Operation::Write,
+        )?;
+        let res = v1_result(
+            self.db_service.lock().await.write_db(
+                &keyvalue.key,
+                &keyvalue.value,
+                None,
+                &keyvalue.namespace,
+            ),
+            |_| {
+                String::from("Wrote key '")
+                    + &keyvalue.key
+                    + "' and value '"
+                    + &keyvalue.value
+                    + "' in namespace '"
+                    + &keyvalue.namespace
+                    + "'"
+            },
         );
 
         Ok(Response::new(StandardResponse {
@@ -54,27 +133,55 @@ impl Database for DatabaseManager {
     }
 
     async fn read(&self, request: Request<Key>) -> Result<Response<ReadResponse>, Status> {
+        let identity = Identity::of(&request);
         let key: Key = request.into_inner();
-        let res: (bool, String, String) = self
-            .db_service
-            .lock()
-            .await
-            .read_db(&key.key, &key.namespace);
+        authorize(
+            &self.access,
+            &identity,
+            Some(&key.namespace),
+            Operation::Read,
+        )?;
+        let res = v1_result(
+            self.db_service
+                .lock()
+                .await
+                .read_db(&key.key, &key.namespace),
+            |value| {
+                String::from("Retrieved value '")
+                    + value
+                    + "' from key '"
+                    + &key.key
+                    + "' in namespace '"
+                    + &key.namespace
+                    + "'"
+            },
+        );
 
         Ok(Response::new(ReadResponse {
             success: res.0,
             message: res.1,
-            result: res.2,
+            result: res.2.unwrap_or_default(),
         }))
     }
 
     async fn delete(&self, request: Request<Key>) -> Result<Response<StandardResponse>, Status> {
+        let identity = Identity::of(&request);
         let key = request.into_inner();
-        let res: (bool, String) = self
-            .db_service
-            .lock()
-            .await
-            .delete_db(&key.key, &key.namespace);
+        authorize(
+            &self.access,
+            &identity,
+            Some(&key.namespace),
+            Operation::Delete,
+        )?;
+        let res = v1_result(
+            self.db_service
+                .lock()
+                .await
+                .delete_db(&key.key, None, &key.namespace),
+            |_| {
+                String::from("Deleted key '") + &key.key + "' in namespace '" + &key.namespace + "'"
+            },
+        );
 
         Ok(Response::new(StandardResponse {
             success: res.0,
@@ -83,17 +190,32 @@ impl Database for DatabaseManager {
     }
 
     async fn search(&self, request: Request<Key>) -> Result<Response<ListResponse>, Status> {
+        let identity = Identity::of(&request);
         let key: Key = request.into_inner();
-        let res: (bool, String, Vec<String>) = self
-            .db_service
-            .lock()
-            .await
-            .search_db(&key.key, &key.namespace);
+        authorize(
+            &self.access,
+            &identity,
+            Some(&key.namespace),
+            Operation::Search,
+        )?;
+        let res = v1_result(
+            self.db_service
+                .lock()
+                .await
+                .search_db(&key.key, &key.namespace),
+            |_| {
+                String::from("Retrieved list of keys containing substring '")
+                    + &key.key
+                    + "' in namespace '"
+                    + &key.namespace
+                    + "'"
+            },
+        );
 
         Ok(Response::new(ListResponse {
             success: res.0,
             message: res.1,
-            result: res.2,
+            result: res.2.unwrap_or_default(),
         }))
     }
 
@@ -101,12 +223,29 @@ impl Database for DatabaseManager {
         &self,
         request: Request<Key>,
     ) -> Result<Response<StandardResponse>, Status> {
+        let identity = Identity::of(&request);
         let key: Key = request.into_inner();
-        let res: (bool, String) = self
-            .db_service
-            .lock()
-            .await
-            .delete_recursively_from_db(&key.key, &key.namespace);
+        authorize(
+            &self.access,
+            &identity,
+            Some(&key.namespace),
+            Operation::DeleteNodes,
+        )?;
+        let res = v1_result(
+            self.db_service
+                .lock()
+                .await
+                .delete_recursively_from_db(&key.key, &key.namespace),
+            |deleted_keys| {
+                let deleted_keys: String =
+                    deleted_keys.iter().map(|key| format!(" {key}")).collect();
+                "Successfully deleted keys: Deleted Keys: ".to_string()
+                    + &deleted_keys
+                    + " in namespace '"
+                    + &key.namespace
+                    + "'."
+            },
+        );
 
         Ok(Response::new(StandardResponse {
             success: res.0,
@@ -118,24 +257,60 @@ impl Database for DatabaseManager {
         &self,
         request: Request<SubtreeInfo>,
     ) -> Result<Response<ListResponse>, Status> {
+        let identity = Identity::of(&request);
         let stinfo: SubtreeInfo = request.into_inner();
-        let res: (bool, String, Vec<String>) = self.db_service.lock().await.nodes_starting_in(
-            &stinfo.node,
-            stinfo.layers,
-            &stinfo.namespace,
+        authorize(
+            &self.access,
+            &identity,
+            Some(&stinfo.namespace),
+            Operation::ListNodes,
+        )?;
+        let res = v1_result(
+            self.db_service.lock().await.nodes_starting_in(
+                &stinfo.node,
+                stinfo.layers,
+                &stinfo.namespace,
+            ),
+            |_| match stinfo.layers.unwrap_or(1) {
+                0 => {
+                    String::from("Retrieved list of keys starting in '")
+                        + &stinfo.node
+                        + "' any number of layers deep (special case layers = '0')"
+                }
+                l => {
+                    String::from("Retrieved list of nodes starting in '")
+                        + &stinfo.node
+                        + "' exactly "
+                        + &l.to_string()
+                        + " layers deep"
+                }
+            },
         );
 
         Ok(Response::new(ListResponse {
             success: res.0,
             message: res.1,
-            result: res.2,
+            result: res.2.unwrap_or_default(),
         }))
     }
 }
 
+/// Converts the outcome of a service operation into the `success`, `message`
+/// and result fields of the v1 API, using `message` to describe a success.
+fn v1_result<T>(
+    result: Result<T, ServiceError>,
+    message: impl FnOnce(&T) -> String,
+) -> (bool, String, Option<T>) {
+    match result {
+        Ok(value) => (true, message(&value), Some(value)),
+        Err(e) => (false, e.to_string(), None),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::memory::MemoryFacade;
     use crate::storage_api::database_client::DatabaseClient;
     use crate::storage_api::database_server::DatabaseServer;
     use serial_test::serial;
@@ -149,7 +324,8 @@ mod tests {
     async fn test_destroy() {
         // Arrange
         let address: SocketAddr = "127.0.0.1:50054".parse().unwrap();
-        let database_manager = DatabaseManager::new();
+        let database_manager =
+            DatabaseManager::new(Box::new(MemoryFacade::new()), &Config::default());
         let server = Server::builder().add_service(DatabaseServer::new(database_manager));
         let server_task = tokio::spawn(server.serve(address.clone()));
 
@@ -200,7 +376,8 @@ mod tests {
     async fn test_write_key_value() {
         // Arrange
         let address: SocketAddr = "127.0.0.1:50054".parse().unwrap();
-        let database_manager = DatabaseManager::new();
+        let database_manager =
+            DatabaseManager::new(Box::new(MemoryFacade::new()), &Config::default());
         let server = Server::builder().add_service(DatabaseServer::new(database_manager));
         let server_task = tokio::spawn(server.serve(address.clone()));
 
@@ -246,7 +423,8 @@ mod tests {
     async fn test_write_empty_key() {
         // Arrange
         let address: SocketAddr = "127.0.0.1:50054".parse().unwrap();
-        let database_manager = DatabaseManager::new();
+        let database_manager =
+            DatabaseManager::new(Box::new(MemoryFacade::new()), &Config::default());
         let server = Server::builder().add_service(DatabaseServer::new(database_manager));
         let server_task = tokio::spawn(server.serve(address.clone()));
 
@@ -295,7 +473,8 @@ mod tests {
 
         // Arrange
         let address: SocketAddr = "127.0.0.1:50054".parse().unwrap();
-        let database_manager = DatabaseManager::new();
+        let database_manager =
+            DatabaseManager::new(Box::new(MemoryFacade::new()), &Config::default());
         let server = Server::builder().add_service(DatabaseServer::new(database_manager));
         let server_task = tokio::spawn(server.serve(address.clone()));
 
@@ -366,7 +545,8 @@ mod tests {
 
         // Arrange
         let address: SocketAddr = "127.0.0.1:50054".parse().unwrap();
-        let database_manager = DatabaseManager::new();
+        let database_manager =
+            DatabaseManager::new(Box::new(MemoryFacade::new()), &Config::default());
         let server = Server::builder().add_service(DatabaseServer::new(database_manager));
         let server_task = tokio::spawn(server.serve(address.clone()));
 
@@ -435,7 +615,8 @@ mod tests {
     async fn test_write_to_nondefault_namespace() {
         // Arrange
         let address: SocketAddr = "127.0.0.1:50054".parse().unwrap();
-        let database_manager = DatabaseManager::new();
+        let database_manager =
+            DatabaseManager::new(Box::new(MemoryFacade::new()), &Config::default());
         let server = Server::builder().add_service(DatabaseServer::new(database_manager));
         let server_task = tokio::spawn(server.serve(address.clone()));
 
@@ -476,6 +657,65 @@ mod tests {
         server_task.abort();
     }
 
+    #[tokio::test]
+    #[serial]
+    async fn test_write_to_read_only_namespace() {
+        // Arrange
+        let address: SocketAddr = "127.0.0.1:50054".parse().unwrap();
+        let config: Config =
+            toml::from_str("[namespaces.ReadOnlyApp]\nread_only = true\n").unwrap();
+        let database_manager = DatabaseManager::new(Box::new(MemoryFacade::new()), &config);
+        let server = Server::builder().add_service(DatabaseServer::new(database_manager));
+        let server_task = tokio::spawn(server.serve(address));
+
+        // Wait for the server to be ready.
+        tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
+
+        let end_addr = "http://127.0.0.1:50054";
+        let endpoint = tonic::transport::Endpoint::from_static(end_addr);
+        let mut client = DatabaseClient::connect(endpoint).await.unwrap();
+
+        // Initial clean up.
+        client.destroy_db(DestroyArguments {}).await.unwrap();
+
+        let key = "Private.Info";
+        let value = "test";
+
+        // Act
+        let response_read_only = client
+            .write(KeyValue {
+                key: key.to_string(),
+                value: value.to_string(),
+                namespace: "ReadOnlyApp".to_string(),
+            })
+            .await
+            .unwrap();
+        let response_writable = client
+            .write(KeyValue {
+                key: key.to_string(),
+                value: value.to_string(),
+                namespace: "AppName".to_string(),
+            })
+            .await
+            .unwrap();
+        let response_delete = client
+            .delete_nodes(Key {
+                key: "Private".to_string(),
+                namespace: "ReadOnlyApp".to_string(),
+            })
+            .await
+            .unwrap();
+
+        // Assert
+        assert!(!response_read_only.into_inner().success);
+        assert!(response_writable.into_inner().success);
+        assert!(!response_delete.into_inner().success);
+
+        // Clean up.
+        let _response_destroy = client.destroy_db(DestroyArguments {}).await.unwrap();
+        server_task.abort();
+    }
+
     // TESTS FOR DELETE FUNCTION
 
     #[tokio::test]
@@ -483,7 +723,8 @@ mod tests {
     async fn test_delete() {
         // Arrange
         let address: SocketAddr = "127.0.0.1:50054".parse().unwrap();
-        let database_manager = DatabaseManager::new();
+        let database_manager =
+            DatabaseManager::new(Box::new(MemoryFacade::new()), &Config::default());
         let server = Server::builder().add_service(DatabaseServer::new(database_manager));
         let server_task = tokio::spawn(server.serve(address.clone()));
 
@@ -540,7 +781,8 @@ mod tests {
     async fn test_delete_key_does_not_exist() {
         // Arrange
         let address: SocketAddr = "127.0.0.1:50054".parse().unwrap();
-        let database_manager = DatabaseManager::new();
+        let database_manager =
+            DatabaseManager::new(Box::new(MemoryFacade::new()), &Config::default());
         let server = Server::builder().add_service(DatabaseServer::new(database_manager));
         let server_task = tokio::spawn(server.serve(address.clone()));
 
@@ -586,7 +828,8 @@ mod tests {
     async fn test_delete_from_nondefault_namespace() {
         // Arrange
         let address: SocketAddr = "127.0.0.1:50054".parse().unwrap();
-        let database_manager = DatabaseManager::new();
+        let database_manager =
+            DatabaseManager::new(Box::new(MemoryFacade::new()), &Config::default());
         let server = Server::builder().add_service(DatabaseServer::new(database_manager));
         let server_task = tokio::spawn(server.serve(address.clone()));
 
@@ -695,7 +938,8 @@ mod tests {
 
         // Arrange
         let address: SocketAddr = "127.0.0.1:50054".parse().unwrap();
-        let database_manager = DatabaseManager::new();
+        let database_manager =
+            DatabaseManager::new(Box::new(MemoryFacade::new()), &Config::default());
         let server = Server::builder().add_service(DatabaseServer::new(database_manager));
         let server_task = tokio::spawn(server.serve(address.clone()));
 
@@ -741,7 +985,8 @@ mod tests {
 
         // Arrange
         let address: SocketAddr = "127.0.0.1:50054".parse().unwrap();
-        let database_manager = DatabaseManager::new();
+        let database_manager =
+            DatabaseManager::new(Box::new(MemoryFacade::new()), &Config::default());
         let server = Server::builder().add_service(DatabaseServer::new(database_manager));
         let server_task = tokio::spawn(server.serve(address.clone()));
 
@@ -787,7 +1032,8 @@ mod tests {
 
         // Arrange
         let address: SocketAddr = "127.0.0.1:50054".parse().unwrap();
-        let database_manager = DatabaseManager::new();
+        let database_manager =
+            DatabaseManager::new(Box::new(MemoryFacade::new()), &Config::default());
         let server = Server::builder().add_service(DatabaseServer::new(database_manager));
         let server_task = tokio::spawn(server.serve(address.clone()));
 
@@ -833,7 +1079,8 @@ mod tests {
 
         // Arrange
         let address: SocketAddr = "127.0.0.1:50054".parse().unwrap();
-        let database_manager = DatabaseManager::new();
+        let database_manager =
+            DatabaseManager::new(Box::new(MemoryFacade::new()), &Config::default());
         let server = Server::builder().add_service(DatabaseServer::new(database_manager));
         let server_task = tokio::spawn(server.serve(address.clone()));
 
@@ -882,7 +1129,8 @@ mod tests {
 
         // Arrange
         let address: SocketAddr = "127.0.0.1:50054".parse().unwrap();
-        let database_manager = DatabaseManager::new();
+        let database_manager =
+            DatabaseManager::new(Box::new(MemoryFacade::new()), &Config::default());
         let server = Server::builder().add_service(DatabaseServer::new(database_manager));
         let server_task = tokio::spawn(server.serve(address.clone()));
 
@@ -928,7 +1176,8 @@ mod tests {
 
         // Arrange
         let address: SocketAddr = "127.0.0.1:50054".parse().unwrap();
-        let database_manager = DatabaseManager::new();
+        let database_manager =
+            DatabaseManager::new(Box::new(MemoryFacade::new()), &Config::default());
         let server = Server::builder().add_service(DatabaseServer::new(database_manager));
         let server_task = tokio::spawn(server.serve(address.clone()));
 
@@ -1072,7 +1321,8 @@ mod tests {
 
         // Arrange
         let address: SocketAddr = "127.0.0.1:50054".parse().unwrap();
-        let database_manager = DatabaseManager::new();
+        let database_manager =
+            DatabaseManager::new(Box::new(MemoryFacade::new()), &Config::default());
         let server = Server::builder().add_service(DatabaseServer::new(database_manager));
         let server_task = tokio::spawn(server.serve(address.clone()));
 
@@ -1232,7 +1482,8 @@ mod tests {
 
         // Arrange
         let address: SocketAddr = "127.0.0.1:50054".parse().unwrap();
-        let database_manager = DatabaseManager::new();
+        let database_manager =
+            DatabaseManager::new(Box::new(MemoryFacade::new()), &Config::default());
         let server = Server::builder().add_service(DatabaseServer::new(database_manager));
         let server_task = tokio::spawn(server.serve(address.clone()));
 
@@ -1412,7 +1663,8 @@ mod tests {
 
         // Arrange
         let address: SocketAddr = "127.0.0.1:50054".parse().unwrap();
-        let database_manager = DatabaseManager::new();
+        let database_manager =
+            DatabaseManager::new(Box::new(MemoryFacade::new()), &Config::default());
         let server = Server::builder().add_service(DatabaseServer::new(database_manager));
         let server_task = tokio::spawn(server.serve(address.clone()));
 
@@ -1575,7 +1827,8 @@ mod tests {
 
         // Arrange
         let address: SocketAddr = "127.0.0.1:50054".parse().unwrap();
-        let database_manager = DatabaseManager::new();
+        let database_manager =
+            DatabaseManager::new(Box::new(MemoryFacade::new()), &Config::default());
         let server = Server::builder().add_service(DatabaseServer::new(database_manager));
         let server_task = tokio::spawn(server.serve(address.clone()));
 
@@ -1624,7 +1877,8 @@ mod tests {
 
         // Arrange
         let address: SocketAddr = "127.0.0.1:50054".parse().unwrap();
-        let database_manager = DatabaseManager::new();
+        let database_manager =
+            DatabaseManager::new(Box::new(MemoryFacade::new()), &Config::default());
         let server = Server::builder().add_service(DatabaseServer::new(database_manager));
         let server_task = tokio::spawn(server.serve(address.clone()));
 
@@ -1672,7 +1926,8 @@ mod tests {
 
         // Arrange
         let address: SocketAddr = "127.0.0.1:50054".parse().unwrap();
-        let database_manager = DatabaseManager::new();
+        let database_manager =
+            DatabaseManager::new(Box::new(MemoryFacade::new()), &Config::default());
         let server = Server::builder().add_service(DatabaseServer::new(database_manager));
         let server_task = tokio::spawn(server.serve(address.clone()));
 
@@ -1725,7 +1980,8 @@ mod tests {
 
         // Arrange
         let address: SocketAddr = "127.0.0.1:50054".parse().unwrap();
-        let database_manager = DatabaseManager::new();
+        let database_manager =
+            DatabaseManager::new(Box::new(MemoryFacade::new()), &Config::default());
         let server = Server::builder().add_service(DatabaseServer::new(database_manager));
         let server_task = tokio::spawn(server.serve(address.clone()));
 
@@ -1780,7 +2036,8 @@ mod tests {
 
         // Arrange
         let address: SocketAddr = "127.0.0.1:50054".parse().unwrap();
-        let database_manager = DatabaseManager::new();
+        let database_manager =
+            DatabaseManager::new(Box::new(MemoryFacade::new()), &Config::default());
         let server = Server::builder().add_service(DatabaseServer::new(database_manager));
         let server_task = tokio::spawn(server.serve(address.clone()));
 
@@ -1836,7 +2093,8 @@ mod tests {
 
         // Arrange
         let address: SocketAddr = "127.0.0.1:50054".parse().unwrap();
-        let database_manager = DatabaseManager::new();
+        let database_manager =
+            DatabaseManager::new(Box::new(MemoryFacade::new()), &Config::default());
         let server = Server::builder().add_service(DatabaseServer::new(database_manager));
         let server_task = tokio::spawn(server.serve(address.clone()));
 
@@ -1882,7 +2140,8 @@ mod tests {
 
         // Arrange
         let address: SocketAddr = "127.0.0.1:50054".parse().unwrap();
-        let database_manager = DatabaseManager::new();
+        let database_manager =
+            DatabaseManager::new(Box::new(MemoryFacade::new()), &Config::default());
         let server = Server::builder().add_service(DatabaseServer::new(database_manager));
         let server_task = tokio::spawn(server.serve(address.clone()));
 
@@ -1928,7 +2187,8 @@ mod tests {
 
         // Arrange
         let address: SocketAddr = "127.0.0.1:50054".parse().unwrap();
-        let database_manager = DatabaseManager::new();
+        let database_manager =
+            DatabaseManager::new(Box::new(MemoryFacade::new()), &Config::default());
         let server = Server::builder().add_service(DatabaseServer::new(database_manager));
         let server_task = tokio::spawn(server.serve(address.clone()));
 
@@ -1973,7 +2233,8 @@ mod tests {
 
         // Arrange
         let address: SocketAddr = "127.0.0.1:50054".parse().unwrap();
-        let database_manager = DatabaseManager::new();
+        let database_manager =
+            DatabaseManager::new(Box::new(MemoryFacade::new()), &Config::default());
         let server = Server::builder().add_service(DatabaseServer::new(database_manager));
         let server_task = tokio::spawn(server.serve(address.clone()));
 
@@ -2018,7 +2279,8 @@ mod tests {
 
         // Arrange
         let address: SocketAddr = "127.0.0.1:50054".parse().unwrap();
-        let database_manager = DatabaseManager::new();
+        let database_manager =
+            DatabaseManager::new(Box::new(MemoryFacade::new()), &Config::default());
         let server = Server::builder().add_service(DatabaseServer::new(database_manager));
         let server_task = tokio::spawn(server.serve(address.clone()));
 
@@ -2060,7 +2322,8 @@ mod tests {
     async fn test_list_nodes_nondefault_namespace() {
         // Arrange
         let address: SocketAddr = "127.0.0.1:50054".parse().unwrap();
-        let database_manager = DatabaseManager::new();
+        let database_manager =
+            DatabaseManager::new(Box::new(MemoryFacade::new()), &Config::default());
         let server = Server::builder().add_service(DatabaseServer::new(database_manager));
         let server_task = tokio::spawn(server.serve(address.clone()));
 
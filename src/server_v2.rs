@@ -0,0 +1,1324 @@
+use futures_core::Stream;
+use std::pin::Pin;
+use tokio_stream::wrappers::errors::BroadcastStreamRecvError;
+use tokio_stream::wrappers::BroadcastStream;
+use tokio_stream::StreamExt;
+use tonic::{Request, Response, Status};
+
+use crate::access::{authorize, Identity, Operation};
+use crate::record::{self, Value};
+use crate::server::DatabaseManager;
+use crate::service::{
+    text_value, ChangeEvent, ImportMode, KeyUpdate, NamespaceInfo, ServiceError, SwapOutcome,
+};
+use crate::storage_api_v2::batch_operation::OperationType;
+use crate::storage_api_v2::database_server::Database;
+use crate::storage_api_v2::import_request::Mode;
+use crate::storage_api_v2::typed_value;
+use crate::storage_api_v2::watch_event::EventType;
+use crate::storage_api_v2::{
+    BatchOperation, CompareAndSwapRequest, CompareAndSwapResponse, DeleteNodesRequest,
+    DeleteNodesResponse, DeleteRequest, DeleteResponse, DestroyDbRequest, DestroyDbResponse,
+    DropNamespaceRequest, DropNamespaceResponse, ExportRequest, ExportResponse, ImportRequest,
+    ImportResponse, ListNamespacesRequest, ListNamespacesResponse, ListNodesRequest,
+    ListNodesResponse, Namespace, NamespaceInfoRequest, NamespaceInfoResponse, ReadRequest,
+    ReadResponse, ReadValueRequest, ReadValueResponse, SearchRequest, SearchResponse, StringList,
+    TypedValue, ValueType, WatchEvent, WatchRequest, WriteBatchRequest, WriteBatchResponse,
+    WriteRequest, WriteResponse, WriteValueRequest,
+};
+
+impl From<ServiceError> for Status {
+    fn from(error: ServiceError) -> Status {
+        match error {
+            ServiceError::NotFound(message) => Status::not_found(message),
+            ServiceError::InvalidArgument(message) => Status::invalid_argument(message),
+            ServiceError::PermissionDenied(message) => Status::permission_denied(message),
+            ServiceError::Internal(message) => Status::internal(message),
+            ServiceError::Unavailable(message) => Status::unavailable(message),
+            ServiceError::Aborted(message) => Status::aborted(message),
+            ServiceError::FailedPrecondition(message) => Status::failed_precondition(message),
+            ServiceError::ResourceExhausted(message) => Status::resource_exhausted(message),
+        }
+    }
+}
+
+impl TryFrom<BatchOperation> for KeyUpdate {
+    type Error = Status;
+
+    fn try_from(operation: BatchOperation) -> Result<KeyUpdate, Status> {
+        match OperationType::from_i32(operation.r#type) {
+            Some(OperationType::Put) => Ok(KeyUpdate::Put {
+                key: operation.key,
+                value: Value::String(operation.value),
+            }),
+            Some(OperationType::Delete) => Ok(KeyUpdate::Delete { key: operation.key }),
+            None => Err(Status::invalid_argument(format!(
+                "Unknown operation type {} for key '{}'",
+                operation.r#type, operation.key
+            ))),
+        }
+    }
+}
+
+impl From<Value> for TypedValue {
+    fn from(value: Value) -> TypedValue {
+        let value = match value {
+            Value::String(value) => typed_value::Value::StringValue(value),
+            Value::Int(value) => typed_value::Value::IntValue(value),
+            Value::Double(value) => typed_value::Value::DoubleValue(value),
+            Value::Bool(value) => typed_value::Value::BoolValue(value),
+            Value::Bytes(value) => typed_value::Value::BytesValue(value),
+            Value::StringList(values) => typed_value::Value::StringListValue(StringList { values }),
+        };
+        TypedValue { value: Some(value) }
+    }
+}
+
+impl TryFrom<Option<TypedValue>> for Value {
+    type Error = Status;
+
+    fn try_from(value: Option<TypedValue>) -> Result<Value, Status> {
+        match value.and_then(|value| value.value) {
+            Some(typed_value::Value::StringValue(value)) => Ok(Value::String(value)),
+            Some(typed_value::Value::IntValue(value)) => Ok(Value::Int(value)),
+            Some(typed_value::Value::DoubleValue(value)) => Ok(Value::Double(value)),
+            Some(typed_value::Value::BoolValue(value)) => Ok(Value::Bool(value)),
+            Some(typed_value::Value::BytesValue(value)) => Ok(Value::Bytes(value)),
+            Some(typed_value::Value::StringListValue(list)) => Ok(Value::StringList(list.values)),
+            None => Err(Status::invalid_argument("Value is not set")),
+        }
+    }
+}
+
+/// Returns the type a ReadValue request expects, or `None` for ANY.
+#[allow(clippy::result_large_err)]
+fn expected_type(value_type: i32) -> Result<Option<record::ValueType>, Status> {
+    match ValueType::from_i32(value_type) {
+        Some(ValueType::Any) => Ok(None),
+        Some(ValueType::String) => Ok(Some(record::ValueType::String)),
+        Some(ValueType::Int) => Ok(Some(record::ValueType::Int)),
+        Some(ValueType::Double) => Ok(Some(record::ValueType::Double)),
+        Some(ValueType::Bool) => Ok(Some(record::ValueType::Bool)),
+        Some(ValueType::Bytes) => Ok(Some(record::ValueType::Bytes)),
+        Some(ValueType::StringList) => Ok(Some(record::ValueType::StringList)),
+        None => Err(Status::invalid_argument(format!(
+            "Unknown value type {value_type}"
+        ))),
+    }
+}
+
+/// Returns the mode of an Import request.
+#[allow(clippy::result_large_err)]
+fn import_mode(mode: i32) -> Result<ImportMode, Status> {
+    match Mode::from_i32(mode) {
+        Some(Mode::Merge) => Ok(ImportMode::Merge),
+        Some(Mode::ReplaceSubtree) => Ok(ImportMode::ReplaceSubtree),
+        Some(Mode::FillMissing) => Ok(ImportMode::FillMissing),
+        None => Err(Status::invalid_argument(format!(
+            "Unknown import mode {mode}"
+        ))),
+    }
+}
+
+impl From<NamespaceInfo> for Namespace {
+    fn from(info: NamespaceInfo) -> Namespace {
+        Namespace {
+            name: info.namespace,
+            key_count: info.key_count,
+            size_bytes: info.size_bytes,
+            read_only: info.read_only,
+        }
+    }
+}
+
+impl WatchRequest {
+    /// Whether `event` changes the key or subtree this request watches.
+    fn matches(&self, event: &ChangeEvent) -> bool {
+        if event.namespace != self.namespace {
+            return false;
+        }
+        if !self.subtree {
+            return event.key == self.key;
+        }
+        self.key.is_empty()
+            || event.key == self.key
+            || event
+                .key
+                .strip_prefix(self.key.as_str())
+                .is_some_and(|rest| rest.starts_with('.'))
+    }
+}
+
+impl From<ChangeEvent> for WatchEvent {
+    fn from(event: ChangeEvent) -> WatchEvent {
+        let event_type = match event.new_value {
+            Some(_) => EventType::Put,
+            None => EventType::Delete,
+        };
+        WatchEvent {
+            r#type: event_type as i32,
+            key: event.key,
+            old_value: event.old_value.as_ref().and_then(Value::as_text),
+            new_value: event.new_value.as_ref().and_then(Value::as_text),
+            old_typed_value: event.old_value.map(TypedValue::from),
+            new_typed_value: event.new_value.map(TypedValue::from),
+        }
+    }
+}
+
+/// Version 2 of the API, which reports failures with gRPC status codes.
+#[tonic::async_trait]
+impl Database for DatabaseManager {
+    type WatchStream = Pin<Box<dyn Stream<Item = Result<WatchEvent, Status>> + Send>>;
+
+    async fn destroy_db(
+        &self,
+        request: Request<DestroyDbRequest>,
+    ) -> Result<Response<DestroyDbResponse>, Status> {
+        authorize(
+            &self.access,
+            &Identity::of(&request),
+            None,
+            Operation::DestroyDb,
+        )?;
+        self.db_service.lock().await.destroy_db()?;
+
+        Ok(Response::new(DestroyDbResponse {}))
+    }
+
+    async fn write(
+        &self,
+        request: Request<WriteRequest>,
+    ) -> Result<Response<WriteResponse>, Status> {
+        let identity = Identity::of(&request);
+        let write = request.into_inner();
+        authorize(
+            &self.access,
+            &identity,
+            Some(&write.namespace),
+            Operation::Write,
+        )?;
+        let version = self.db_service.lock().await.write_with_ttl(
+            &write.key,
+            Value::from(write.value.as_str()),
+            write.expected_version,
+            write.ttl_ms,
+            &write.namespace,
+        )?;
+
+        Ok(Response::new(WriteResponse { version }))
+    }
+
+    async fn read(&self, request: Request<ReadRequest>) -> Result<Response<ReadResponse>, Status> {
+        let identity = Identity::of(&request);
+        let read = request.into_inner();
+        authorize(
+            &self.access,
+            &identity,
+            Some(&read.namespace),
+            Operation::Read,
+        )?;
+        let record = self
+            .db_service
+            .lock()
+            .await
+            .read_record(&read.key, &read.namespace)?;
+
+        Ok(Response::new(ReadResponse {
+            value: text_value(&read.key, &read.namespace, &record.value)?,
+            version: record.version,
+            modified_ms: record.modified_ms,
+            expires_ms: record.expires_ms,
+        }))
+    }
+
+    async fn write_value(
+        &self,
+        request: Request<WriteValueRequest>,
+    ) -> Result<Response<WriteResponse>, Status> {
+        let identity = Identity::of(&request);
+        let write = request.into_inner();
+        authorize(
+            &self.access,
+            &identity,
+            Some(&write.namespace),
+            Operation::WriteValue,
+        )?;
+        let value = Value::try_from(write.value)?;
+        let version = self.db_service.lock().await.write_with_ttl(
+            &write.key,
+            value,
+            write.expected_version,
+            write.ttl_ms,
+            &write.namespace,
+        )?;
+
+        Ok(Response::new(WriteResponse { version }))
+    }
+
+    async fn read_value(
+        &self,
+        request: Request<ReadValueRequest>,
+    ) -> Result<Response<ReadValueResponse>, Status> {
+        let identity = Identity::of(&request);
+        let read = request.into_inner();
+        authorize(
+            &self.access,
+            &identity,
+            Some(&read.namespace),
+            Operation::ReadValue,
+        )?;
+        let value_type = expected_type(read.r#type)?;
+        let record =
+            self.db_service
+                .lock()
+                .await
+                .read_typed(&read.key, value_type, &read.namespace)?;
+
+        Ok(Response::new(ReadValueResponse {
+            value: Some(TypedValue::from(record.value)),
+            version: record.version,
+            modified_ms: record.modified_ms,
+            expires_ms: record.expires_ms,
+        }))
+    }
+
+    async fn delete(
+        &self,
+        request: Request<DeleteRequest>,
+    ) -> Result<Response<DeleteResponse>, Status> {
+        let identity = Identity::of(&request);
+        let delete = request.into_inner();
+        authorize(
+            &self.access,
+            &identity,
+            Some(&delete.namespace),
+            Operation::Delete,
+        )?;
+        self.db_service.lock().await.delete_db(
+            &delete.key,
+            delete.expected_version,
+            &delete.namespace,
+        )?;
+
+        Ok(Response::new(DeleteResponse {}))
+    }
+
+    async fn compare_and_swap(
+        &self,
+        request: Request<CompareAndSwapRequest>,
+    ) -> Result<Response<CompareAndSwapResponse>, Status> {
+        let identity = Identity::of(&request);
+        let swap = request.into_inner();
+        authorize(
+            &self.access,
+            &identity,
+            Some(&swap.namespace),
+            Operation::CompareAndSwap,
+        )?;
+        let outcome = self.db_service.lock().await.compare_and_swap(
+            &swap.key,
+            swap.expected_value.as_deref(),
+            &swap.value,
+            &swap.namespace,
+        )?;
+
+        Ok(Response::new(match outcome {
+            SwapOutcome::Swapped => CompareAndSwapResponse {
+                swapped: true,
+                current_value: Some(swap.value),
+            },
+            SwapOutcome::Conflict { current_value } => CompareAndSwapResponse {
+                swapped: false,
+                current_value,
+            },
+        }))
+    }
+
+    async fn write_batch(
+        &self,
+        request: Request<WriteBatchRequest>,
+    ) -> Result<Response<WriteBatchResponse>, Status> {
+        let identity = Identity::of(&request);
+        let batch = request.into_inner();
+        authorize(
+            &self.access,
+            &identity,
+            Some(&batch.namespace),
+            Operation::WriteBatch,
+        )?;
+        let updates = batch
+            .operations
+            .into_iter()
+            .map(KeyUpdate::try_from)
+            .collect::<Result<Vec<_>, Status>>()?;
+        self.db_service
+            .lock()
+            .await
+            .write_batch(&updates, &batch.namespace)?;
+
+        Ok(Response::new(WriteBatchResponse {}))
+    }
+
+    async fn search(
+        &self,
+        request: Request<SearchRequest>,
+    ) -> Result<Response<SearchResponse>, Status> {
+        let identity = Identity::of(&request);
+        let search = request.into_inner();
+        authorize(
+            &self.access,
+            &identity,
+            Some(&search.namespace),
+            Operation::Search,
+        )?;
+        let keys = self
+            .db_service
+            .lock()
+            .await
+            .search_db(&search.substring, &search.namespace)?;
+
+        Ok(Response::new(SearchResponse { keys }))
+    }
+
+    async fn delete_nodes(
+        &self,
+        request: Request<DeleteNodesRequest>,
+    ) -> Result<Response<DeleteNodesResponse>, Status> {
+        let identity = Identity::of(&request);
+        let delete = request.into_inner();
+        authorize(
+            &self.access,
+            &identity,
+            Some(&delete.namespace),
+            Operation::DeleteNodes,
+        )?;
+        let deleted_keys = self
+            .db_service
+            .lock()
+            .await
+            .delete_recursively_from_db(&delete.node, &delete.namespace)?;
+
+        Ok(Response::new(DeleteNodesResponse { deleted_keys }))
+    }
+
+    async fn list_nodes(
+        &self,
+        request: Request<ListNodesRequest>,
+    ) -> Result<Response<ListNodesResponse>, Status> {
+        let identity = Identity::of(&request);
+        let list = request.into_inner();
+        authorize(
+            &self.access,
+            &identity,
+            Some(&list.namespace),
+            Operation::ListNodes,
+        )?;
+        let nodes = self.db_service.lock().await.nodes_starting_in(
+            &list.node,
+            list.layers,
+            &list.namespace,
+        )?;
+
+        Ok(Response::new(ListNodesResponse { nodes }))
+    }
+
+    async fn list_namespaces(
+        &self,
+        request: Request<ListNamespacesRequest>,
+    ) -> Result<Response<ListNamespacesResponse>, Status> {
+        authorize(
+            &self.access,
+            &Identity::of(&request),
+            None,
+            Operation::ListNamespaces,
+        )?;
+        let namespaces = self.db_service.lock().await.list_namespaces()?;
+
+        Ok(Response::new(ListNamespacesResponse {
+            namespaces: namespaces.into_iter().map(Namespace::from).collect(),
+        }))
+    }
+
+    async fn namespace_info(
+        &self,
+        request: Request<NamespaceInfoRequest>,
+    ) -> Result<Response<NamespaceInfoResponse>, Status> {
+        let identity = Identity::of(&request);
+        let info = request.into_inner();
+        authorize(
+            &self.access,
+            &identity,
+            Some(&info.namespace),
+            Operation::NamespaceInfo,
+        )?;
+        let namespace = self
+            .db_service
+            .lock()
+            .await
+            .namespace_info(&info.namespace)?;
+
+        Ok(Response::new(NamespaceInfoResponse {
+            namespace: Some(Namespace::from(namespace)),
+        }))
+    }
+
+    async fn drop_namespace(
+        &self,
+        request: Request<DropNamespaceRequest>,
+    ) -> Result<Response<DropNamespaceResponse>, Status> {
+        let identity = Identity::of(&request);
+        let drop = request.into_inner();
+        authorize(
+            &self.access,
+            &identity,
+            Some(&drop.namespace),
+            Operation::DropNamespace,
+        )?;
+        self.db_service
+            .lock()
+            .await
+            .drop_namespace(&drop.namespace)?;
+
+        Ok(Response::new(DropNamespaceResponse {}))
+    }
+
+    async fn export(
+        &self,
+        request: Request<ExportRequest>,
+    ) -> Result<Response<ExportResponse>, Status> {
+        let identity = Identity::of(&request);
+        let export = request.into_inner();
+        authorize(
+            &self.access,
+            &identity,
+            Some(&export.namespace),
+            Operation::Export,
+        )?;
+        let tree = self
+            .db_service
+            .lock()
+            .await
+            .export(&export.node, &export.namespace)?;
+
+        Ok(Response::new(ExportResponse {
+            json: tree.to_string(),
+        }))
+    }
+
+    async fn import(
+        &self,
+        request: Request<ImportRequest>,
+    ) -> Result<Response<ImportResponse>, Status> {
+        let identity = Identity::of(&request);
+        let import = request.into_inner();
+        authorize(
+            &self.access,
+            &identity,
+            Some(&import.namespace),
+            Operation::Import,
+        )?;
+        let mode = import_mode(import.mode)?;
+        let document: serde_json::Value = serde_json::from_str(&import.json)
+            .map_err(|e| Status::invalid_argument(format!("Invalid JSON document: {e}")))?;
+        let report = self.db_service.lock().await.import(
+            &document,
+            &import.node,
+            mode,
+            import.dry_run,
+            &import.namespace,
+        )?;
+
+        Ok(Response::new(ImportResponse {
+            created_keys: report.created,
+            updated_keys: report.updated,
+            deleted_keys: report.deleted,
+            unchanged_keys: report.unchanged,
+        }))
+    }
+
+    async fn watch(
+        &self,
+        request: Request<WatchRequest>,
+    ) -> Result<Response<Self::WatchStream>, Status> {
+        let identity = Identity::of(&request);
+        let watch = request.into_inner();
+        authorize(
+            &self.access,
+            &identity,
+            Some(&watch.namespace),
+            Operation::Watch,
+        )?;
+        let events = self.db_service.lock().await.subscribe();
+
+        let stream = BroadcastStream::new(events).filter_map(move |event| match event {
+            Ok(event) if watch.matches(&event) => Some(Ok(WatchEvent::from(event))),
+            Ok(_) => None,
+            Err(BroadcastStreamRecvError::Lagged(missed)) => Some(Err(Status::resource_exhausted(
+                format!("Watcher fell behind and missed {missed} changes"),
+            ))),
+        });
+        Ok(Response::new(Box::pin(stream)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::memory::MemoryFacade;
+    use crate::storage_api_v2::database_client::DatabaseClient;
+    use crate::storage_api_v2::database_server::DatabaseServer;
+    use tokio::net::TcpListener;
+    use tokio::task::JoinHandle;
+    use tokio_stream::wrappers::TcpListenerStream;
+    use tonic::transport::{Channel, Server};
+    use tonic::Code;
+
+    /// Serves the v2 API for `config` on a free port and connects a client to it.
+    async fn start_server(config: &Config) -> (DatabaseClient<Channel>, JoinHandle<()>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let database_manager = DatabaseManager::new(Box::new(MemoryFacade::new()), config);
+        let server = Server::builder().add_service(DatabaseServer::new(database_manager));
+        let server_task = tokio::spawn(async move {
+            server
+                .serve_with_incoming(TcpListenerStream::new(listener))
+                .await
+                .unwrap()
+        });
+
+        let channel = Channel::from_shared(format!("http://{address}"))
+            .unwrap()
+            .connect()
+            .await
+            .unwrap();
+        (DatabaseClient::new(channel), server_task)
+    }
+
+    fn write_request(key: &str, value: &str, namespace: &str) -> WriteRequest {
+        WriteRequest {
+            key: key.to_string(),
+            value: value.to_string(),
+            namespace: namespace.to_string(),
+            expected_version: None,
+            ttl_ms: None,
+        }
+    }
+
+    #[test]
+    fn service_error_to_status_test() {
+        let status = Status::from(ServiceError::NotFound(String::from("Key not found")));
+
+        assert_eq!(status.code(), Code::NotFound);
+        assert_eq!(status.message(), "Key not found");
+        assert_eq!(
+            Status::from(ServiceError::Unavailable(String::new())).code(),
+            Code::Unavailable
+        );
+        assert_eq!(
+            Status::from(ServiceError::ResourceExhausted(String::new())).code(),
+            Code::ResourceExhausted
+        );
+    }
+
+    #[test]
+    fn watch_request_matches_test() {
+        let change = |key: &str, namespace: &str| ChangeEvent {
+            namespace: namespace.to_string(),
+            key: key.to_string(),
+            old_value: None,
+            new_value: Some(Value::from("1")),
+        };
+        let watch = |key: &str, subtree: bool| WatchRequest {
+            key: key.to_string(),
+            subtree,
+            namespace: "".to_string(),
+        };
+
+        assert!(watch("Vehicle.Speed", false).matches(&change("Vehicle.Speed", "")));
+        assert!(!watch("Vehicle.Speed", false).matches(&change("Vehicle.Speed.Unit", "")));
+        assert!(!watch("Vehicle.Speed", false).matches(&change("Vehicle.Speed", "AppName")));
+        assert!(watch("Vehicle", true).matches(&change("Vehicle", "")));
+        assert!(watch("Vehicle", true).matches(&change("Vehicle.Speed", "")));
+        assert!(!watch("Vehicle", true).matches(&change("VehicleSpeed", "")));
+        assert!(watch("", true).matches(&change("Private.Info", "")));
+        assert!(!watch("", true).matches(&change("Private.Info", "AppName")));
+    }
+
+    #[tokio::test]
+    async fn test_watch_subtree() {
+        // Arrange
+        let (mut client, server_task) = start_server(&Config::default()).await;
+        client
+            .write(write_request("Vehicle.Infotainment.Radio.Volume", "10", ""))
+            .await
+            .unwrap();
+        let mut events = client
+            .watch(WatchRequest {
+                key: "Vehicle.Infotainment.Radio".to_string(),
+                subtree: true,
+                namespace: "".to_string(),
+            })
+            .await
+            .unwrap()
+            .into_inner();
+
+        // Act
+        client
+            .write(write_request("Vehicle.Infotainment.Radio.Volume", "12", ""))
+            .await
+            .unwrap();
+        client
+            .write(write_request("Vehicle.Infotainment.Display", "on", ""))
+            .await
+            .unwrap();
+        client
+            .write(write_request(
+                "Vehicle.Infotainment.Radio.Volume",
+                "12",
+                "OtherApp",
+            ))
+            .await
+            .unwrap();
+        client
+            .delete(DeleteRequest {
+                key: "Vehicle.Infotainment.Radio.Volume".to_string(),
+                namespace: "".to_string(),
+                expected_version: None,
+            })
+            .await
+            .unwrap();
+        let event_put = events.message().await.unwrap().unwrap();
+        let event_delete = events.message().await.unwrap().unwrap();
+
+        // Assert
+        assert_eq!(
+            event_put,
+            WatchEvent {
+                r#type: EventType::Put as i32,
+                key: "Vehicle.Infotainment.Radio.Volume".to_string(),
+                old_value: Some("10".to_string()),
+                new_value: Some("12".to_string()),
+                old_typed_value: Some(Value::from("10").into()),
+                new_typed_value: Some(Value::from("12").into()),
+            }
+        );
+        assert_eq!(
+            event_delete,
+            WatchEvent {
+                r#type: EventType::Delete as i32,
+                key: "Vehicle.Infotainment.Radio.Volume".to_string(),
+                old_value: Some("12".to_string()),
+                new_value: None,
+                old_typed_value: Some(Value::from("12").into()),
+                new_typed_value: None,
+            }
+        );
+
+        // Clean up.
+        server_task.abort();
+    }
+
+    #[tokio::test]
+    async fn test_write_and_read() {
+        // Arrange
+        let (mut client, server_task) = start_server(&Config::default()).await;
+
+        // Act
+        client
+            .write(write_request(
+                "Vehicle.Cabin.Door.Row1.Open",
+                "true",
+                "Body",
+            ))
+            .await
+            .unwrap();
+        let response_read = client
+            .read(ReadRequest {
+                key: "Vehicle.Cabin.Door.Row1.Open".to_string(),
+                namespace: "Body".to_string(),
+            })
+            .await
+            .unwrap();
+
+        // Assert
+        assert_eq!(response_read.into_inner().value, "true");
+
+        // Clean up.
+        server_task.abort();
+    }
+
+    #[tokio::test]
+    async fn test_expected_version() {
+        // Arrange
+        let (mut client, server_task) = start_server(&Config::default()).await;
+        let write = |value: &str, expected_version: Option<u64>| WriteRequest {
+            expected_version,
+            ..write_request("Vehicle.Cabin.HVAC.Station.Row1.Left.FanSpeed", value, "")
+        };
+        let read = || ReadRequest {
+            key: "Vehicle.Cabin.HVAC.Station.Row1.Left.FanSpeed".to_string(),
+            namespace: "".to_string(),
+        };
+        let delete = |expected_version: Option<u64>| DeleteRequest {
+            key: "Vehicle.Cabin.HVAC.Station.Row1.Left.FanSpeed".to_string(),
+            namespace: "".to_string(),
+            expected_version,
+        };
+
+        // Act
+        let response_create = client.write(write("40", Some(0))).await.unwrap();
+        let response_read = client.read(read()).await.unwrap();
+        let response_update = client.write(write("60", Some(1))).await.unwrap();
+        let response_stale_write = client.write(write("80", Some(1))).await;
+        let response_stale_delete = client.delete(delete(Some(1))).await;
+        let response_delete = client.delete(delete(Some(2))).await;
+
+        // Assert
+        assert_eq!(response_create.into_inner().version, 1);
+        let read = response_read.into_inner();
+        assert_eq!((read.value.as_str(), read.version), ("40", 1));
+        assert!(read.modified_ms > 0);
+        assert_eq!(response_update.into_inner().version, 2);
+        assert_eq!(response_stale_write.unwrap_err().code(), Code::Aborted);
+        assert_eq!(response_stale_delete.unwrap_err().code(), Code::Aborted);
+        assert!(response_delete.is_ok());
+
+        // Clean up.
+        server_task.abort();
+    }
+
+    #[tokio::test]
+    async fn test_ttl() {
+        // Arrange
+        let (mut client, server_task) = start_server(&Config::default()).await;
+        let write = |ttl_ms: Option<u64>| WriteRequest {
+            ttl_ms,
+            ..write_request("Vehicle.Cabin.Infotainment.Navigation.Route", "home", "")
+        };
+        let read = || ReadRequest {
+            key: "Vehicle.Cabin.Infotainment.Navigation.Route".to_string(),
+            namespace: "".to_string(),
+        };
+
+        // Act
+        client.write(write(Some(3_600_000))).await.unwrap();
+        let response_expiring = client.read(read()).await.unwrap();
+        let response_zero = client.write(write(Some(0))).await;
+        client.write(write(None)).await.unwrap();
+        let response_permanent = client.read(read()).await.unwrap();
+
+        // Assert
+        let expiring = response_expiring.into_inner();
+        assert_eq!(expiring.expires_ms, Some(expiring.modified_ms + 3_600_000));
+        assert_eq!(response_zero.unwrap_err().code(), Code::InvalidArgument);
+        assert_eq!(response_permanent.into_inner().expires_ms, None);
+
+        // Clean up.
+        server_task.abort();
+    }
+
+    #[tokio::test]
+    async fn test_typed_values() {
+        // Arrange
+        let (mut client, server_task) = start_server(&Config::default()).await;
+        let write = |key: &str, value: Value| WriteValueRequest {
+            key: key.to_string(),
+            value: Some(value.into()),
+            namespace: "Profile".to_string(),
+            expected_version: None,
+            ttl_ms: None,
+        };
+        let read = |key: &str, value_type: ValueType| ReadValueRequest {
+            key: key.to_string(),
+            namespace: "Profile".to_string(),
+            r#type: value_type as i32,
+        };
+        let thumbnail = Value::Bytes(vec![0x89, b'P', b'N', b'G', 0]);
+
+        // Act
+        client
+            .write_value(write("Vehicle.Cabin.Seat.Row1.Heating", Value::Int(2)))
+            .await
+            .unwrap();
+        client
+            .write_value(write("Picture.Thumbnail", thumbnail.clone()))
+            .await
+            .unwrap();
+        let response_unset = client
+            .write_value(WriteValueRequest {
+                value: None,
+                ..write("Picture.Thumbnail", Value::Bool(true))
+            })
+            .await;
+        let response_int = client
+            .read_value(read("Vehicle.Cabin.Seat.Row1.Heating", ValueType::Int))
+            .await;
+        let response_any = client
+            .read_value(read("Picture.Thumbnail", ValueType::Any))
+            .await;
+        let response_mismatch = client
+            .read_value(read("Vehicle.Cabin.Seat.Row1.Heating", ValueType::String))
+            .await;
+        let response_text = client
+            .read(ReadRequest {
+                key: "Vehicle.Cabin.Seat.Row1.Heating".to_string(),
+                namespace: "Profile".to_string(),
+            })
+            .await;
+        let response_bytes_text = client
+            .read(ReadRequest {
+                key: "Picture.Thumbnail".to_string(),
+                namespace: "Profile".to_string(),
+            })
+            .await;
+
+        // Assert
+        assert_eq!(response_unset.unwrap_err().code(), Code::InvalidArgument);
+        assert_eq!(
+            response_int.unwrap().into_inner().value,
+            Some(Value::Int(2).into())
+        );
+        assert_eq!(
+            response_any.unwrap().into_inner().value,
+            Some(thumbnail.into())
+        );
+        assert_eq!(
+            response_mismatch.unwrap_err().code(),
+            Code::FailedPrecondition
+        );
+        assert_eq!(response_text.unwrap().into_inner().value, "2");
+        assert_eq!(
+            response_bytes_text.unwrap_err().code(),
+            Code::FailedPrecondition
+        );
+
+        // Clean up.
+        server_task.abort();
+    }
+
+    #[tokio::test]
+    async fn test_error_codes() {
+        // Arrange
+        let config: Config =
+            toml::from_str("[namespaces.ReadOnlyApp]\nread_only = true\n").unwrap();
+        let (mut client, server_task) = start_server(&config).await;
+
+        // Act
+        let response_missing_key = client
+            .read(ReadRequest {
+                key: "Vehicle.Speed".to_string(),
+                namespace: "".to_string(),
+            })
+            .await;
+        let response_missing_delete = client
+            .delete(DeleteRequest {
+                key: "Vehicle.Speed".to_string(),
+                namespace: "".to_string(),
+                expected_version: None,
+            })
+            .await;
+        let response_empty_key = client.write(write_request("", "1", "")).await;
+        let response_read_only = client
+            .write(write_request("Vehicle.Speed", "1", "ReadOnlyApp"))
+            .await;
+        let response_negative_layers = client
+            .list_nodes(ListNodesRequest {
+                node: "Vehicle".to_string(),
+                layers: Some(-1),
+                namespace: "".to_string(),
+            })
+            .await;
+        let response_missing_node = client
+            .list_nodes(ListNodesRequest {
+                node: "Vehicle".to_string(),
+                layers: None,
+                namespace: "".to_string(),
+            })
+            .await;
+
+        // Assert
+        assert_eq!(response_missing_key.unwrap_err().code(), Code::NotFound);
+        assert_eq!(response_missing_delete.unwrap_err().code(), Code::NotFound);
+        assert_eq!(
+            response_empty_key.unwrap_err().code(),
+            Code::InvalidArgument
+        );
+        assert_eq!(
+            response_read_only.unwrap_err().code(),
+            Code::PermissionDenied
+        );
+        assert_eq!(
+            response_negative_layers.unwrap_err().code(),
+            Code::InvalidArgument
+        );
+        assert_eq!(response_missing_node.unwrap_err().code(), Code::NotFound);
+
+        // Clean up.
+        server_task.abort();
+    }
+
+    #[tokio::test]
+    async fn test_compare_and_swap() {
+        // Arrange
+        let (mut client, server_task) = start_server(&Config::default()).await;
+        client
+            .write(write_request(
+                "Vehicle.Cabin.HVAC.IsAirConditioningActive",
+                "false",
+                "",
+            ))
+            .await
+            .unwrap();
+        let swap = |expected_value: Option<&str>, value: &str| CompareAndSwapRequest {
+            key: "Vehicle.Cabin.HVAC.IsAirConditioningActive".to_string(),
+            expected_value: expected_value.map(str::to_string),
+            value: value.to_string(),
+            namespace: "".to_string(),
+        };
+
+        // Act
+        let response_swapped = client
+            .compare_and_swap(swap(Some("false"), "true"))
+            .await
+            .unwrap();
+        let response_conflict = client
+            .compare_and_swap(swap(Some("false"), "true"))
+            .await
+            .unwrap();
+        let response_absent = client.compare_and_swap(swap(None, "true")).await.unwrap();
+
+        // Assert
+        assert_eq!(
+            response_swapped.into_inner(),
+            CompareAndSwapResponse {
+                swapped: true,
+                current_value: Some("true".to_string()),
+            }
+        );
+        assert_eq!(
+            response_conflict.into_inner(),
+            CompareAndSwapResponse {
+                swapped: false,
+                current_value: Some("true".to_string()),
+            }
+        );
+        assert!(!response_absent.into_inner().swapped);
+
+        // Clean up.
+        server_task.abort();
+    }
+
+    #[tokio::test]
+    async fn test_write_batch() {
+        // Arrange
+        let (mut client, server_task) = start_server(&Config::default()).await;
+        client
+            .write(write_request(
+                "Vehicle.Cabin.Seat.Row1.Position",
+                "3",
+                "Profile",
+            ))
+            .await
+            .unwrap();
+        let operation = |operation_type: OperationType, key: &str, value: &str| BatchOperation {
+            r#type: operation_type as i32,
+            key: key.to_string(),
+            value: value.to_string(),
+        };
+        let read = |key: &str| ReadRequest {
+            key: key.to_string(),
+            namespace: "Profile".to_string(),
+        };
+
+        // Act
+        let response_batch = client
+            .write_batch(WriteBatchRequest {
+                operations: vec![
+                    operation(OperationType::Put, "Vehicle.Cabin.Seat.Row1.Heating", "2"),
+                    operation(
+                        OperationType::Put,
+                        "Vehicle.Infotainment.Radio.Volume",
+                        "12",
+                    ),
+                    operation(
+                        OperationType::Delete,
+                        "Vehicle.Cabin.Seat.Row1.Position",
+                        "",
+                    ),
+                ],
+                namespace: "Profile".to_string(),
+            })
+            .await;
+        let response_invalid_batch = client
+            .write_batch(WriteBatchRequest {
+                operations: vec![
+                    operation(OperationType::Put, "Vehicle.Infotainment.Radio.Volume", "0"),
+                    operation(OperationType::Put, "", "1"),
+                ],
+                namespace: "Profile".to_string(),
+            })
+            .await;
+        let response_unknown_type = client
+            .write_batch(WriteBatchRequest {
+                operations: vec![BatchOperation {
+                    r#type: 7,
+                    key: "Vehicle.Speed".to_string(),
+                    value: String::new(),
+                }],
+                namespace: "Profile".to_string(),
+            })
+            .await;
+        let response_heating = client.read(read("Vehicle.Cabin.Seat.Row1.Heating")).await;
+        let response_volume = client.read(read("Vehicle.Infotainment.Radio.Volume")).await;
+        let response_position = client.read(read("Vehicle.Cabin.Seat.Row1.Position")).await;
+
+        // Assert
+        assert!(response_batch.is_ok());
+        assert_eq!(
+            response_invalid_batch.unwrap_err().code(),
+            Code::InvalidArgument
+        );
+        assert_eq!(
+            response_unknown_type.unwrap_err().code(),
+            Code::InvalidArgument
+        );
+        assert_eq!(response_heating.unwrap().into_inner().value, "2");
+        assert_eq!(response_volume.unwrap().into_inner().value, "12");
+        assert_eq!(response_position.unwrap_err().code(), Code::NotFound);
+
+        // Clean up.
+        server_task.abort();
+    }
+
+    #[tokio::test]
+    async fn test_search_list_and_delete_nodes() {
+        // Arrange
+        let (mut client, server_task) = start_server(&Config::default()).await;
+        for key in [
+            "Vehicle.Infotainment.Radio.Volume",
+            "Vehicle.Infotainment.Radio.Station",
+            "Vehicle.Infotainment.Display",
+            "Vehicle.Speed",
+        ] {
+            client.write(write_request(key, "1", "")).await.unwrap();
+        }
+
+        // Act
+        let response_search = client
+            .search(SearchRequest {
+                substring: "Radio".to_string(),
+                namespace: "".to_string(),
+            })
+            .await
+            .unwrap();
+        let response_list = client
+            .list_nodes(ListNodesRequest {
+                node: "Vehicle.Infotainment".to_string(),
+                layers: Some(1),
+                namespace: "".to_string(),
+            })
+            .await
+            .unwrap();
+        let response_delete = client
+            .delete_nodes(DeleteNodesRequest {
+                node: "Vehicle.Infotainment".to_string(),
+                namespace: "".to_string(),
+            })
+            .await
+            .unwrap();
+        let response_search_after_delete = client
+            .search(SearchRequest {
+                substring: "Infotainment".to_string(),
+                namespace: "".to_string(),
+            })
+            .await
+            .unwrap();
+
+        // Assert
+        assert_eq!(
+            response_search.into_inner().keys,
+            vec![
+                "Vehicle.Infotainment.Radio.Station",
+                "Vehicle.Infotainment.Radio.Volume"
+            ]
+        );
+        assert_eq!(
+            response_list.into_inner().nodes,
+            vec!["Vehicle.Infotainment.Display", "Vehicle.Infotainment.Radio"]
+        );
+        assert_eq!(response_delete.into_inner().deleted_keys.len(), 3);
+        assert!(response_search_after_delete.into_inner().keys.is_empty());
+
+        // Clean up.
+        server_task.abort();
+    }
+
+    #[tokio::test]
+    async fn test_namespaces() {
+        // Arrange
+        let (mut client, server_task) = start_server(&Config::default()).await;
+        client
+            .write(write_request("Vehicle.Speed", "10", ""))
+            .await
+            .unwrap();
+        client
+            .write(write_request("Private.Info", "test", "AppName"))
+            .await
+            .unwrap();
+
+        // Act
+        let response_list = client
+            .list_namespaces(ListNamespacesRequest {})
+            .await
+            .unwrap();
+        let response_drop = client
+            .drop_namespace(DropNamespaceRequest {
+                namespace: "AppName".to_string(),
+            })
+            .await;
+        let response_info = client
+            .namespace_info(NamespaceInfoRequest {
+                namespace: "AppName".to_string(),
+            })
+            .await
+            .unwrap();
+
+        // Assert
+        let namespaces = response_list.into_inner().namespaces;
+        assert_eq!(
+            namespaces
+                .iter()
+                .map(|namespace| (namespace.name.as_str(), namespace.key_count))
+                .collect::<Vec<_>>(),
+            vec![("", 1), ("AppName", 1)]
+        );
+        assert!(namespaces[1].size_bytes > "Private.Infotest".len() as u64);
+        assert!(response_drop.is_ok());
+        assert_eq!(response_info.into_inner().namespace.unwrap().key_count, 0);
+
+        // Clean up.
+        server_task.abort();
+    }
+
+    #[tokio::test]
+    async fn test_export() {
+        // Arrange
+        let (mut client, server_task) = start_server(&Config::default()).await;
+        client
+            .write(write_request("Vehicle.Infotainment", "yes", ""))
+            .await
+            .unwrap();
+        client
+            .write(write_request(
+                "Vehicle.Infotainment.Radio.CurrentStation",
+                "wdr 4",
+                "",
+            ))
+            .await
+            .unwrap();
+        let export = |node: &str| ExportRequest {
+            node: node.to_string(),
+            namespace: "".to_string(),
+        };
+
+        // Act
+        let response_export = client.export(export("Vehicle.Infotainment")).await;
+        let response_missing = client.export(export("Vehicle.Cabin")).await;
+
+        // Assert
+        let json: serde_json::Value =
+            serde_json::from_str(&response_export.unwrap().into_inner().json).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "Vehicle": {
+                    "Infotainment": {
+                        "$value": "yes",
+                        "Radio": { "CurrentStation": "wdr 4" }
+                    }
+                }
+            })
+        );
+        assert_eq!(response_missing.unwrap_err().code(), Code::NotFound);
+
+        // Clean up.
+        server_task.abort();
+    }
+
+    #[tokio::test]
+    async fn test_import() {
+        // Arrange
+        let (mut client, server_task) = start_server(&Config::default()).await;
+        client
+            .write(write_request("Vehicle.Speed", "50", "Profile"))
+            .await
+            .unwrap();
+        let import = |json: &str, mode: Mode, dry_run: bool| ImportRequest {
+            json: json.to_string(),
+            node: "".to_string(),
+            namespace: "Profile".to_string(),
+            mode: mode as i32,
+            dry_run,
+        };
+
+        // Act
+        let response_dry_run = client
+            .import(import(
+                r#"{"Vehicle": {"Cabin.Seat.Row1.Heating": 2}}"#,
+                Mode::ReplaceSubtree,
+                true,
+            ))
+            .await
+            .unwrap();
+        let response_fill = client
+            .import(import(
+                r#"{"Vehicle.Speed": "60", "Vehicle.Cabin.Seat.Row1.Heating": 2}"#,
+                Mode::FillMissing,
+                false,
+            ))
+            .await
+            .unwrap();
+        let response_malformed = client.import(import("{", Mode::Merge, false)).await;
+        let response_unknown_mode = client
+            .import(ImportRequest {
+                mode: 7,
+                ..import("{}", Mode::Merge, false)
+            })
+            .await;
+        let response_read = client
+            .read_value(ReadValueRequest {
+                key: "Vehicle.Cabin.Seat.Row1.Heating".to_string(),
+                namespace: "Profile".to_string(),
+                r#type: ValueType::Int as i32,
+            })
+            .await;
+
+        // Assert
+        let dry_run = response_dry_run.into_inner();
+        assert_eq!(
+            dry_run.created_keys,
+            vec!["Vehicle.Cabin.Seat.Row1.Heating"]
+        );
+        assert_eq!(dry_run.deleted_keys, vec!["Vehicle.Speed"]);
+        let fill = response_fill.into_inner();
+        assert_eq!(fill.created_keys, vec!["Vehicle.Cabin.Seat.Row1.Heating"]);
+        assert_eq!(fill.unchanged_keys, vec!["Vehicle.Speed"]);
+        assert_eq!(
+            response_malformed.unwrap_err().code(),
+            Code::InvalidArgument
+        );
+        assert_eq!(
+            response_unknown_mode.unwrap_err().code(),
+            Code::InvalidArgument
+        );
+        assert_eq!(
+            response_read.unwrap().into_inner().value,
+            Some(TypedValue::from(Value::Int(2)))
+        );
+
+        // Clean up.
+        server_task.abort();
+    }
+}
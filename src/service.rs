@@ -1,457 +1,2678 @@
-use crate::facade::RocksDbFacade;
+use crate::backend::{BatchOperation, NamespaceUsage, StorageBackend};
+use crate::config::{Config, NamespacePolicy};
+use crate::facade::FacadeError;
+use crate::keys;
+use crate::record::{now_ms, Record, Value, ValueType};
+use crate::tree;
 use home::home_dir;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
 use std::path::Path;
 use std::path::PathBuf;
+use tokio::sync::broadcast;
+
+/// Number of change events buffered for each watcher before it misses events.
+const CHANGE_EVENT_CAPACITY: usize = 1024;
+
+/// Returns the default database directory: `AGLPersistentStorageAPI` in the
+/// home directory, falling back to `/etc/default` and the working directory.
+pub fn default_db_path() -> String {
+    if home_dir()
+        .expect("Unable to get your home dir!")
+        .try_exists()
+        .expect("Can't check existence of directory")
+    {
+        let mut db_path = home_dir().expect("Unable to get your home dir!");
+        db_path.push("AGLPersistentStorageAPI");
+        return db_path.into_os_string().into_string().unwrap();
+    } else if Path::new("/etc/")
+        .try_exists()
+        .expect("Can't check existence of directory")
+    {
+        let mut db_path = PathBuf::new();
+        db_path.push("/etc/default/AGLPersistentStorageAPI");
+        return db_path.into_os_string().into_string().unwrap();
+    } else {
+        let mut db_path = PathBuf::new();
+        db_path.push("AGLPersistentStorageAPI");
+        return db_path.into_os_string().into_string().unwrap();
+    }
+}
+
+/// Reason why an operation of `DbService` failed.
+///
+/// Every variant carries a message for the client. The v1 API passes it on in
+/// `message`, the v2 API maps the variant to a gRPC status code.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ServiceError {
+    /// The key or node does not exist.
+    NotFound(String),
+    /// The request itself is malformed, e.g. an empty key.
+    InvalidArgument(String),
+    /// The namespace policy forbids the operation.
+    PermissionDenied(String),
+    /// The database failed to carry out the operation.
+    Internal(String),
+    /// The database could not be opened.
+    Unavailable(String),
+    /// The key does not have the version the request expects.
+    Aborted(String),
+    /// The value of the key has a different type than the request expects.
+    FailedPrecondition(String),
+    /// The change would exceed a quota of the namespace.
+    ResourceExhausted(String),
+}
+
+impl fmt::Display for ServiceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ServiceError::NotFound(message)
+            | ServiceError::InvalidArgument(message)
+            | ServiceError::PermissionDenied(message)
+            | ServiceError::Internal(message)
+            | ServiceError::Unavailable(message)
+            | ServiceError::Aborted(message)
+            | ServiceError::FailedPrecondition(message)
+            | ServiceError::ResourceExhausted(message) => write!(f, "{message}"),
+        }
+    }
+}
+
+impl std::error::Error for ServiceError {}
+
+impl ServiceError {
+    /// Classifies a failure of the storage backend, with `message` for the client.
+    fn from_facade(error: &FacadeError, message: String) -> ServiceError {
+        match error {
+            FacadeError::NotOpen => ServiceError::Unavailable(message),
+            FacadeError::NotFound => ServiceError::NotFound(message),
+            FacadeError::Backend(_) | FacadeError::InvalidUtf8(_) | FacadeError::Corruption(_) => {
+                ServiceError::Internal(message)
+            }
+        }
+    }
+}
+
+/// A key that was written or deleted.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChangeEvent {
+    pub namespace: String,
+    pub key: String,
+    /// Value before the change; `None` if the key did not exist.
+    pub old_value: Option<Value>,
+    /// Value after the change; `None` if the key was deleted.
+    pub new_value: Option<Value>,
+}
+
+/// A change of one key requested as part of `DbService::write_batch`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum KeyUpdate {
+    /// Writes `value` under `key`.
+    Put { key: String, value: Value },
+    /// Deletes `key`, if it exists.
+    Delete { key: String },
+}
+
+/// Outcome of `DbService::compare_and_swap`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SwapOutcome {
+    /// The new value was written.
+    Swapped,
+    /// The key did not have the expected value and was left unchanged.
+    Conflict {
+        /// Actual value of the key; `None` if it does not exist.
+        current_value: Option<String>,
+    },
+}
+
+/// How `DbService::import` treats the keys that exist already.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportMode {
+    /// Writes all given keys and leaves the other keys alone.
+    Merge,
+    /// Writes all given keys and deletes the other keys of the subtree.
+    ReplaceSubtree,
+    /// Only writes the given keys that do not exist.
+    FillMissing,
+}
+
+/// Keys changed by `DbService::import`, or that would be changed by a dry run.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportReport {
+    pub created: Vec<String>,
+    pub updated: Vec<String>,
+    pub deleted: Vec<String>,
+    /// Given keys that are left as they are, because they have the given value
+    /// already or exist in `ImportMode::FillMissing`.
+    pub unchanged: Vec<String>,
+}
+
+/// Keys of a namespace that may have expired, listed by
+/// `DbService::expiry_scans` and checked bit by bit with
+/// `DbService::remove_expired_keys`.
+#[derive(Debug)]
+pub struct ExpiryScan {
+    namespace: String,
+    /// Namespaced keys that are still to be checked.
+    keys: Vec<Vec<u8>>,
+    /// Writes with a time to live to the namespace when the keys were listed.
+    ttl_writes: u64,
+    /// Whether a checked key expires later.
+    expiring_left: bool,
+}
+
+impl ExpiryScan {
+    /// Whether all keys of the scan are checked.
+    pub fn is_done(&self) -> bool {
+        self.keys.is_empty()
+    }
+}
+
+/// Contents and policy of a namespace.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NamespaceInfo {
+    pub namespace: String,
+    pub key_count: u64,
+    /// Size of the keys and values in bytes, before compression.
+    pub size_bytes: u64,
+    pub read_only: bool,
+}
+
+/// Change of one key for the quota check: the sizes of its value (see
+/// `Value::size`) before and after, `None` if the key does not exist.
+#[derive(Debug, Clone, Copy)]
+struct SizeChange {
+    key_len: u64,
+    old_size: Option<u64>,
+    new_size: Option<u64>,
+}
+
+impl SizeChange {
+    fn apply_to(&self, usage: &mut NamespaceUsage) {
+        if let Some(old_size) = self.old_size {
+            usage.keys = usage.keys.saturating_sub(1);
+            usage.bytes = usage.bytes.saturating_sub(self.key_len + old_size);
+        }
+        if let Some(new_size) = self.new_size {
+            usage.keys += 1;
+            usage.bytes += self.key_len + new_size;
+        }
+    }
+}
 
 #[derive(Debug)]
 pub struct DbService {
-    rocks_db_facade: RocksDbFacade,
+    backend: Box<dyn StorageBackend>,
+    db_path: String,
+    namespaces: BTreeMap<String, NamespacePolicy>,
+    events: broadcast::Sender<ChangeEvent>,
+    /// Whether the opened database was migrated to the current key format.
+    migrated: bool,
+    /// Usage of the namespaces whose policy limits it. It is counted once
+    /// when first needed and then kept up to date with every change.
+    usage: BTreeMap<String, NamespaceUsage>,
+    /// Last version given out in each namespace, read from the database when
+    /// first needed (see `last_version`).
+    versions: BTreeMap<String, u64>,
+    /// Whether each namespace may hold keys that expire, read from its marker
+    /// (see `keys::expiry_marker_key`) when first needed.
+    expiring: BTreeMap<String, bool>,
+    /// Writes with a time to live to each namespace, so that an expiry scan
+    /// only clears the marker if there were none while it ran.
+    ttl_writes: BTreeMap<String, u64>,
+    /// Whether `seed_defaults` writes to read-only namespaces.
+    seed_read_only: bool,
 }
 
 impl DbService {
-    pub fn new() -> DbService {
+    /// Creates a service that stores its data in `backend`, using the database
+    /// path and namespace policies from `config`.
+    pub fn new(backend: Box<dyn StorageBackend>, config: &Config) -> DbService {
         DbService {
-            rocks_db_facade: RocksDbFacade::new(),
+            backend,
+            db_path: config.database.path.clone(),
+            namespaces: config.namespaces.clone(),
+            events: broadcast::channel(CHANGE_EVENT_CAPACITY).0,
+            migrated: false,
+            usage: BTreeMap::new(),
+            versions: BTreeMap::new(),
+            expiring: BTreeMap::new(),
+            ttl_writes: BTreeMap::new(),
+            seed_read_only: config.defaults.write_read_only,
         }
     }
 
-    fn set_path_db(&mut self) -> String {
-        // Database path is defined here
-        if home_dir()
-            .expect("Unable to get your home dir!")
-            .try_exists()
-            .expect("Can't check existence of directory")
-        {
-            let mut db_path = home_dir().expect("Unable to get your home dir!");
-            db_path.push("AGLPersistentStorageAPI");
-            return db_path.into_os_string().into_string().unwrap();
-        } else if Path::new("/etc/")
-            .try_exists()
-            .expect("Can't check existence of directory")
-        {
-            let mut db_path = PathBuf::new();
-            db_path.push("/etc/default/AGLPersistentStorageAPI");
-            return db_path.into_os_string().into_string().unwrap();
-        } else {
-            let mut db_path = PathBuf::new();
-            db_path.push("AGLPersistentStorageAPI");
-            return db_path.into_os_string().into_string().unwrap();
+    /// Returns a receiver for the changes made from now on.
+    pub fn subscribe(&self) -> broadcast::Receiver<ChangeEvent> {
+        self.events.subscribe()
+    }
+
+    /// Reads and decodes the record stored under the namespaced key. Expired
+    /// records count as deleted and fail with `FacadeError::NotFound`.
+    fn load_record(&mut self, namespace_key: &[u8]) -> Result<Record, FacadeError> {
+        match self.stored_record(namespace_key)? {
+            Some((record, _)) if !record.is_expired(now_ms()) => Ok(record),
+            _ => Err(FacadeError::NotFound),
         }
     }
 
-    fn open_db(&mut self) -> (bool, String) {
-        let db_path = self.set_path_db();
-        match self.rocks_db_facade.open_db(db_path.as_str()) {
-            Ok(()) => {
-                return (
-                    true,
-                    String::from("Opened database at path '") + db_path.as_str() + "'",
-                )
+    /// Reads the record stored under the namespaced key together with the size
+    /// of its value, including expired records that were not removed yet.
+    fn stored_record(
+        &mut self,
+        namespace_key: &[u8],
+    ) -> Result<Option<(Record, u64)>, FacadeError> {
+        match self.backend.read_db(namespace_key) {
+            Ok(bytes) => {
+                let record = Record::decode(&bytes)?;
+                let size = record.value.size();
+                Ok(Some((record, size)))
             }
-            Err(e) => {
-                return (
-                    false,
-                    String::from("Error when trying to open database at path '")
-                        + &db_path.as_str()
-                        + "': "
-                        + &e.to_string(),
-                )
+            Err(FacadeError::NotFound) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Lists the keys in `namespace` that start with `prefix` and have not
+    /// expired, without the namespace. Records are only read if keys of the
+    /// namespace may expire.
+    fn keys_with_prefix(
+        &mut self,
+        prefix: &str,
+        namespace: &str,
+    ) -> Result<Vec<Vec<u8>>, FacadeError> {
+        if !self.may_expire(namespace)? {
+            return self.stored_keys_with_prefix(prefix, namespace);
+        }
+        let now_ms = now_ms();
+        let mut live_keys = Vec::new();
+        for key in self.stored_keys_with_prefix(prefix, namespace)? {
+            match self.stored_record(&keys::namespace_key(namespace, &key))? {
+                Some((record, _)) if record.is_expired(now_ms) => {}
+                _ => live_keys.push(key),
             }
         }
+        Ok(live_keys)
     }
 
-    pub fn destroy_db(&mut self) -> (bool, String) {
-        let (is_open, msg) = self.open_db();
-        let db_path = self.set_path_db();
-        if !is_open {
-            return (false, msg);
+    /// Lists the keys in `namespace` that start with `prefix` like
+    /// `keys_with_prefix`, including expired keys that were not removed yet.
+    fn stored_keys_with_prefix(
+        &mut self,
+        prefix: &str,
+        namespace: &str,
+    ) -> Result<Vec<Vec<u8>>, FacadeError> {
+        let namespace_prefix = keys::namespace_prefix(namespace);
+        let keys = self
+            .backend
+            .list_keys_with_prefix(&keys::namespace_key(namespace, prefix.as_bytes()))?;
+        Ok(keys
+            .into_iter()
+            .map(|key| key[namespace_prefix.len()..].to_vec())
+            .collect())
+    }
+
+    /// Whether `namespace` may hold keys that expire, i.e. was written with a
+    /// time to live since an expiry scan last found none.
+    fn may_expire(&mut self, namespace: &str) -> Result<bool, FacadeError> {
+        if let Some(expiring) = self.expiring.get(namespace) {
+            return Ok(*expiring);
         }
-        match self.rocks_db_facade.destroy_db(db_path.as_str()) {
-            Ok(()) => {
-                return (
-                    true,
-                    String::from("Destroyed database at path '") + db_path.as_str() + "'",
-                )
+        let expiring = match self.backend.read_db(&keys::expiry_marker_key(namespace)) {
+            Ok(_) => true,
+            Err(FacadeError::NotFound) => false,
+            Err(e) => return Err(e),
+        };
+        self.expiring.insert(namespace.to_string(), expiring);
+        Ok(expiring)
+    }
+
+    /// Reads the record of `key`, or `None` if the key does not exist or has
+    /// expired, together with the size of its value, or `None` if nothing is
+    /// stored.
+    ///
+    /// An expired record still takes up space until it is overwritten or
+    /// removed, so its size counts for the quotas.
+    fn current_record(
+        &mut self,
+        key: &str,
+        namespace: &str,
+    ) -> Result<(Option<Record>, Option<u64>), ServiceError> {
+        match self.stored_record(&keys::namespace_key(namespace, key.as_bytes())) {
+            Ok(Some((record, size))) if record.is_expired(now_ms()) => Ok((None, Some(size))),
+            Ok(stored) => Ok(stored.unzip()),
+            Err(e) => Err(ServiceError::from_facade(
+                &e,
+                String::from("Error when trying to read the current value of key '")
+                    + key
+                    + "' in namespace '"
+                    + namespace
+                    + "': "
+                    + &e.to_string(),
+            )),
+        }
+    }
+
+    /// Reads the value of the namespaced key before it is changed, for the
+    /// change event.
+    ///
+    /// Skipped if nobody is subscribed, so that changes cost no extra read then.
+    fn previous_value(&mut self, namespace_key: &[u8]) -> Result<Option<Value>, ServiceError> {
+        if self.events.receiver_count() == 0 {
+            return Ok(None);
+        }
+        match self.load_record(namespace_key) {
+            Ok(record) => Ok(Some(record.value)),
+            Err(FacadeError::NotFound) => Ok(None),
+            Err(e) => Err(ServiceError::from_facade(
+                &e,
+                String::from("Error when trying to read the previous value of key '")
+                    + &String::from_utf8_lossy(namespace_key)
+                    + "': "
+                    + &e.to_string(),
+            )),
+        }
+    }
+
+    fn notify(
+        &self,
+        key: &str,
+        namespace: &str,
+        old_value: Option<Value>,
+        new_value: Option<Value>,
+    ) {
+        // Sending only fails if nobody is subscribed.
+        let _ = self.events.send(ChangeEvent {
+            namespace: namespace.to_string(),
+            key: key.to_string(),
+            old_value,
+            new_value,
+        });
+    }
+
+    /// Returns the directory of the database files.
+    pub fn db_path(&self) -> &str {
+        &self.db_path
+    }
+
+    /// Fails if the policy of `namespace` forbids modifications.
+    fn check_writable(&self, namespace: &str) -> Result<(), ServiceError> {
+        match self.namespaces.get(namespace) {
+            Some(policy) if policy.read_only => Err(ServiceError::PermissionDenied(
+                String::from("Namespace '") + namespace + "' is read-only and cannot be modified",
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks `changes` against the quotas of `namespace` and returns the usage
+    /// of the namespace after them, or `None` if its usage is not tracked.
+    ///
+    /// Changes that do not increase the usage are accepted even if the
+    /// namespace exceeds its quota, e.g. after the quota was lowered, so that
+    /// it can always be cleaned up.
+    fn check_quota(
+        &mut self,
+        namespace: &str,
+        changes: &[SizeChange],
+    ) -> Result<Option<NamespaceUsage>, ServiceError> {
+        let policy = match self.namespaces.get(namespace) {
+            Some(policy) => policy.clone(),
+            None => return Ok(None),
+        };
+        for change in changes {
+            let new_size = match change.new_size {
+                Some(new_size) => new_size,
+                None => continue,
+            };
+            if let Some(max_key_length) = policy.max_key_length {
+                if change.key_len > max_key_length {
+                    return Err(ServiceError::ResourceExhausted(
+                        String::from("Key of ")
+                            + &change.key_len.to_string()
+                            + " bytes exceeds the maximum key length of "
+                            + &max_key_length.to_string()
+                            + " bytes in namespace '"
+                            + namespace
+                            + "'",
+                    ));
+                }
             }
-            Err(e) => {
-                return (
-                    false,
-                    String::from("Error when trying to destroy database at path '")
+            if let Some(max_value_size) = policy.max_value_size {
+                if new_size > max_value_size {
+                    return Err(ServiceError::ResourceExhausted(
+                        String::from("Value of ")
+                            + &new_size.to_string()
+                            + " bytes exceeds the maximum value size of "
+                            + &max_value_size.to_string()
+                            + " bytes in namespace '"
+                            + namespace
+                            + "'",
+                    ));
+                }
+            }
+        }
+        if !policy.limits_usage() {
+            return Ok(None);
+        }
+
+        let usage = match self.usage.get(namespace) {
+            Some(usage) => *usage,
+            None => {
+                let usage = self.count_usage(namespace).map_err(|e| {
+                    ServiceError::from_facade(
+                        &e,
+                        String::from("Error when trying to count the keys in namespace '")
+                            + namespace
+                            + "': "
+                            + &e.to_string(),
+                    )
+                })?;
+                self.usage.insert(namespace.to_string(), usage);
+                usage
+            }
+        };
+        let mut new_usage = usage;
+        for change in changes {
+            change.apply_to(&mut new_usage);
+        }
+        if let Some(max_keys) = policy.max_keys {
+            if new_usage.keys > max_keys && new_usage.keys > usage.keys {
+                return Err(ServiceError::ResourceExhausted(
+                    String::from("Namespace '")
+                        + namespace
+                        + "' would hold "
+                        + &new_usage.keys.to_string()
+                        + " keys, more than its quota of "
+                        + &max_keys.to_string(),
+                ));
+            }
+        }
+        if let Some(max_bytes) = policy.max_bytes {
+            if new_usage.bytes > max_bytes && new_usage.bytes > usage.bytes {
+                return Err(ServiceError::ResourceExhausted(
+                    String::from("Namespace '")
+                        + namespace
+                        + "' would hold "
+                        + &new_usage.bytes.to_string()
+                        + " bytes, more than its quota of "
+                        + &max_bytes.to_string(),
+                ));
+            }
+        }
+        Ok(Some(new_usage))
+    }
+
+    /// Counts the keys in `namespace` and the size of their keys and values
+    /// (see `Value::size`), as the quotas do. Values that cannot be decoded
+    /// count with their stored size.
+    fn count_usage(&mut self, namespace: &str) -> Result<NamespaceUsage, FacadeError> {
+        let mut usage = NamespaceUsage::default();
+        for key in self.stored_keys_with_prefix("", namespace)? {
+            let bytes = match self.backend.read_db(&keys::namespace_key(namespace, &key)) {
+                Ok(bytes) => bytes,
+                Err(FacadeError::NotFound) => continue,
+                Err(e) => return Err(e),
+            };
+            let size =
+                Record::decode(&bytes).map_or(bytes.len() as u64, |record| record.value.size());
+            usage.keys += 1;
+            usage.bytes += key.len() as u64 + size;
+        }
+        Ok(usage)
+    }
+
+    /// Records the usage returned by `check_quota` once the change is applied.
+    fn update_usage(&mut self, namespace: &str, usage: Option<NamespaceUsage>) {
+        if let Some(usage) = usage {
+            self.usage.insert(namespace.to_string(), usage);
+        }
+    }
+
+    /// Returns the last version given out in `namespace`.
+    ///
+    /// Every write takes its version from this counter, which is kept outside
+    /// the namespace, so a key that is deleted, expires or is dropped with its
+    /// namespace and then written again never gets a version it had before.
+    fn last_version(&mut self, namespace: &str) -> Result<u64, ServiceError> {
+        if let Some(version) = self.versions.get(namespace) {
+            return Ok(*version);
+        }
+        let version = match self.backend.read_db(&keys::version_key(namespace)) {
+            Ok(bytes) => <[u8; 8]>::try_from(bytes.as_slice())
+                .map(u64::from_be_bytes)
+                .map_err(|_| FacadeError::Corruption(format!("Invalid version counter {bytes:?}"))),
+            Err(FacadeError::NotFound) => Ok(0),
+            Err(e) => Err(e),
+        }
+        .map_err(|e| {
+            ServiceError::from_facade(
+                &e,
+                String::from("Error when trying to read the last version in namespace '")
+                    + namespace
+                    + "': "
+                    + &e.to_string(),
+            )
+        })?;
+        self.versions.insert(namespace.to_string(), version);
+        Ok(version)
+    }
+
+    /// Whether the usage of `namespace` is tracked for its quotas.
+    fn tracks_usage(&self, namespace: &str) -> bool {
+        self.namespaces
+            .get(namespace)
+            .is_some_and(NamespacePolicy::limits_usage)
+    }
+
+    /// Opens the database and, the first time, migrates it to the current key
+    /// format.
+    fn open_db(&mut self) -> Result<(), ServiceError> {
+        let db_path = self.db_path.clone();
+        self.backend.open_db(db_path.as_str()).map_err(|e| {
+            ServiceError::Unavailable(
+                String::from("Error when trying to open database at path '")
+                    + db_path.as_str()
+                    + "': "
+                    + &e.to_string(),
+            )
+        })?;
+        if !self.migrated {
+            let migrated_keys = keys::migrate(self.backend.as_mut()).map_err(|e| {
+                ServiceError::Unavailable(
+                    String::from("Error when trying to migrate database at path '")
                         + db_path.as_str()
                         + "': "
                         + &e.to_string(),
                 )
+            })?;
+            if migrated_keys > 0 {
+                log::info!("Migrated {migrated_keys} keys to the current key format");
             }
+            self.migrated = true;
         }
+        Ok(())
     }
 
-    pub fn write_db(&mut self, key: &str, value: &str, namespace: &str) -> (bool, String) {
-        let (is_open, msg) = self.open_db();
-        if !is_open {
-            return (false, msg);
-        }
+    pub fn destroy_db(&mut self) -> Result<(), ServiceError> {
+        self.open_db()?;
+        let db_path = self.db_path.clone();
+        // The recreated database is empty and only needs its format recorded.
+        self.migrated = false;
+        self.usage.clear();
+        self.versions.clear();
+        self.expiring.clear();
+        self.ttl_writes.clear();
+        self.backend.destroy_db(db_path.as_str()).map_err(|e| {
+            ServiceError::from_facade(
+                &e,
+                String::from("Error when trying to destroy database at path '")
+                    + db_path.as_str()
+                    + "': "
+                    + &e.to_string(),
+            )
+        })
+    }
+
+    /// Writes the string `value` under `key`, see `write_value`.
+    pub fn write_db(
+        &mut self,
+        key: &str,
+        value: &str,
+        expected_version: Option<u64>,
+        namespace: &str,
+    ) -> Result<u64, ServiceError> {
+        self.write_value(key, Value::from(value), expected_version, namespace)
+    }
+
+    /// Writes `value` under `key` and returns the new version of the key.
+    ///
+    /// If `expected_version` is given, the write fails with
+    /// `ServiceError::Aborted` unless the key currently has that version, where
+    /// a key that does not exist has version 0.
+    pub fn write_value(
+        &mut self,
+        key: &str,
+        value: Value,
+        expected_version: Option<u64>,
+        namespace: &str,
+    ) -> Result<u64, ServiceError> {
+        self.write_with_ttl(key, value, expected_version, None, namespace)
+    }
+
+    /// Writes `value` under `key` like `write_value`. If `ttl_ms` is given, the
+    /// key expires that many milliseconds after the write: from then on it is
+    /// treated as deleted, until `remove_expired` removes it from the database.
+    /// Without `ttl_ms`, the key does not expire, even if it did before.
+    pub fn write_with_ttl(
+        &mut self,
+        key: &str,
+        value: Value,
+        expected_version: Option<u64>,
+        ttl_ms: Option<u64>,
+        namespace: &str,
+    ) -> Result<u64, ServiceError> {
+        self.open_db()?;
+        self.check_writable(namespace)?;
         if key.is_empty() {
-            return (
-                false,
+            return Err(ServiceError::InvalidArgument(
+                String::from("Error when trying to write key '")
+                    + key
+                    + "' and value '"
+                    + &value.to_string()
+                    + "': Key cannot be empty string.",
+            ));
+        }
+        if ttl_ms == Some(0) {
+            return Err(ServiceError::InvalidArgument(
+                String::from("Error when trying to write key '")
+                    + key
+                    + "' in namespace '"
+                    + namespace
+                    + "': The time to live must be positive.",
+            ));
+        }
+
+        let (current, current_size) = self.current_record(key, namespace)?;
+        check_version(key, namespace, current.as_ref(), expected_version)?;
+        let last_version = self.last_version(namespace)?;
+        let mut record = Record::new(value, next_version(current.as_ref(), last_version));
+        if let Some(ttl_ms) = ttl_ms {
+            record = record.with_ttl(ttl_ms);
+        }
+        let encoded = record.encode();
+        let usage = self.check_quota(
+            namespace,
+            &[SizeChange {
+                key_len: key.len() as u64,
+                old_size: current_size,
+                new_size: Some(record.value.size()),
+            }],
+        )?;
+        let mut operations = vec![
+            BatchOperation::Put {
+                key: keys::namespace_key(namespace, key.as_bytes()),
+                value: encoded,
+            },
+            version_operation(namespace, record.version),
+        ];
+        let marks_expiring = ttl_ms.is_some()
+            && !self.may_expire(namespace).map_err(|e| {
+                ServiceError::from_facade(
+                    &e,
+                    String::from("Error when trying to read the expiry marker of namespace '")
+                        + namespace
+                        + "': "
+                        + &e.to_string(),
+                )
+            })?;
+        if marks_expiring {
+            operations.push(BatchOperation::Put {
+                key: keys::expiry_marker_key(namespace),
+                value: Vec::new(),
+            });
+        }
+        self.backend.write_batch(&operations).map_err(|e| {
+            ServiceError::from_facade(
+                &e,
                 String::from("Error when trying to write key '")
                     + key
                     + "' and value '"
+                    + &record.value.to_string()
+                    + "' in namespace '"
+                    + namespace
+                    + "': "
+                    + &e.to_string(),
+            )
+        })?;
+        self.update_usage(namespace, usage);
+        let version = record.version;
+        self.versions.insert(namespace.to_string(), version);
+        if ttl_ms.is_some() {
+            self.expiring.insert(namespace.to_string(), true);
+            *self.ttl_writes.entry(namespace.to_string()).or_default() += 1;
+        }
+        self.notify(
+            key,
+            namespace,
+            current.map(|current| current.value),
+            Some(record.value),
+        );
+        Ok(version)
+    }
+
+    /// Writes `value` under `key` only if the key currently has the string value
+    /// `expected_value` or, if that is `None`, does not exist.
+    ///
+    /// Only string values are compared: if the key holds a value of another
+    /// type, e.g. an int written by WriteValue, this fails with
+    /// `ServiceError::FailedPrecondition` rather than comparing its text and
+    /// replacing it by a string. The check and the write are atomic as long as
+    /// the service is shared behind a lock, as `DatabaseManager` does.
+    pub fn compare_and_swap(
+        &mut self,
+        key: &str,
+        expected_value: Option<&str>,
+        value: &str,
+        namespace: &str,
+    ) -> Result<SwapOutcome, ServiceError> {
+        self.open_db()?;
+        self.check_writable(namespace)?;
+        if key.is_empty() {
+            return Err(ServiceError::InvalidArgument(
+                String::from("Error when trying to compare and swap key '")
+                    + key
+                    + "' with value '"
                     + value
                     + "': Key cannot be empty string.",
-            );
+            ));
         }
 
-        let namespace_key = format!("{namespace}_.{key}");
-        match self.rocks_db_facade.write_db(namespace_key.as_str(), value) {
-            Ok(()) => {
-                return (
-                    true,
-                    String::from("Wrote key '")
+        let current_value = match self.current_record(key, namespace)?.0 {
+            Some(Record {
+                value: Value::String(current_value),
+                ..
+            }) => Some(current_value),
+            Some(current) => {
+                return Err(ServiceError::FailedPrecondition(
+                    String::from("Key '")
                         + key
-                        + "' and value '"
-                        + value
                         + "' in namespace '"
                         + namespace
-                        + "'",
-                )
+                        + "' holds a value of type "
+                        + &current.value.value_type().to_string()
+                        + ", but only string values can be compared and swapped",
+                ))
             }
-            Err(e) => {
-                return (
-                    false,
-                    String::from("Error when trying to write key '")
+            None => None,
+        };
+        if current_value.as_deref() != expected_value {
+            return Ok(SwapOutcome::Conflict { current_value });
+        }
+        self.write_with_ttl(key, Value::from(value), None, None, namespace)?;
+        Ok(SwapOutcome::Swapped)
+    }
+
+    /// Reads the value of `key` as text. Fails with
+    /// `ServiceError::FailedPrecondition` for bytes and string lists.
+    pub fn read_db(&mut self, key: &str, namespace: &str) -> Result<String, ServiceError> {
+        let record = self.read_record(key, namespace)?;
+        text_value(key, namespace, &record.value)
+    }
+
+    /// Reads the record of `key` like `read_record`, but fails with
+    /// `ServiceError::FailedPrecondition` if `value_type` is given and the value
+    /// has a different type.
+    pub fn read_typed(
+        &mut self,
+        key: &str,
+        value_type: Option<ValueType>,
+        namespace: &str,
+    ) -> Result<Record, ServiceError> {
+        let record = self.read_record(key, namespace)?;
+        match value_type {
+            Some(value_type) if record.value.value_type() != value_type => {
+                Err(ServiceError::FailedPrecondition(
+                    String::from("Key '")
+                        + key
+                        + "' in namespace '"
+                        + namespace
+                        + "' holds a value of type "
+                        + &record.value.value_type().to_string()
+                        + ", not "
+                        + &value_type.to_string(),
+                ))
+            }
+            _ => Ok(record),
+        }
+    }
+
+    /// Reads the value of `key` together with its version and modification time.
+    pub fn read_record(&mut self, key: &str, namespace: &str) -> Result<Record, ServiceError> {
+        self.open_db()?;
+        let namespace_key = keys::namespace_key(namespace, key.as_bytes());
+        self.load_record(&namespace_key).map_err(|e| {
+            ServiceError::from_facade(
+                &e,
+                String::from("Error when trying to retrieve from key '")
+                    + key
+                    + "' in namespace '"
+                    + namespace
+                    + "': "
+                    + &e.to_string(),
+            )
+        })
+    }
+
+    /// Checks whether `key` has a value in `namespace`. Fails if the backend
+    /// cannot tell, e.g. because of an I/O error.
+    pub fn check_if_key_exists(
+        &mut self,
+        key: &str,
+        namespace: &str,
+    ) -> Result<bool, ServiceError> {
+        match self.load_record(&keys::namespace_key(namespace, key.as_bytes())) {
+            Ok(_record) => Ok(true),
+            Err(FacadeError::NotFound) => Ok(false),
+            Err(e) => Err(ServiceError::from_facade(
+                &e,
+                String::from("Error when trying to check whether key '")
+                    + key
+                    + "' exists in namespace '"
+                    + namespace
+                    + "': "
+                    + &e.to_string(),
+            )),
+        }
+    }
+
+    /// Deletes `key`. If `expected_version` is given, the deletion fails with
+    /// `ServiceError::Aborted` unless the key currently has that version.
+    pub fn delete_db(
+        &mut self,
+        key: &str,
+        expected_version: Option<u64>,
+        namespace: &str,
+    ) -> Result<(), ServiceError> {
+        self.open_db()?;
+        self.check_writable(namespace)?;
+
+        let (current, current_size) = match self.current_record(key, namespace)? {
+            (Some(current), Some(current_size)) => (current, current_size),
+            _ => {
+                return Err(ServiceError::NotFound(
+                    String::from("Key '")
+                        + key
+                        + "' does not exist in namespace '"
+                        + namespace
+                        + "'!",
+                ))
+            }
+        };
+        check_version(key, namespace, Some(&current), expected_version)?;
+        let usage = self.check_quota(
+            namespace,
+            &[SizeChange {
+                key_len: key.len() as u64,
+                old_size: Some(current_size),
+                new_size: None,
+            }],
+        )?;
+        self.backend
+            .delete_db(&keys::namespace_key(namespace, key.as_bytes()))
+            .map_err(|e| {
+                ServiceError::from_facade(
+                    &e,
+                    String::from("Error when trying to delete key '")
                         + key
-                        + "' and value '"
-                        + value
                         + "' in namespace '"
                         + namespace
                         + "': "
                         + &e.to_string(),
                 )
+            })?;
+        self.update_usage(namespace, usage);
+        self.notify(key, namespace, Some(current.value), None);
+        Ok(())
+    }
+
+    /// Applies the puts and deletes in `updates` to `namespace` atomically:
+    /// either all of them take effect or none does. Deleting a key that does
+    /// not exist is not an error.
+    pub fn write_batch(
+        &mut self,
+        updates: &[KeyUpdate],
+        namespace: &str,
+    ) -> Result<(), ServiceError> {
+        self.open_db()?;
+        self.check_writable(namespace)?;
+        self.apply_updates(updates, namespace, false, Vec::new())
+    }
+
+    /// Applies `updates` like `write_batch` or, if `dry_run` is set, only
+    /// checks that they could be applied. Does not check whether the namespace
+    /// is writable. `extra_operations` are written in the same batch.
+    fn apply_updates(
+        &mut self,
+        updates: &[KeyUpdate],
+        namespace: &str,
+        dry_run: bool,
+        extra_operations: Vec<BatchOperation>,
+    ) -> Result<(), ServiceError> {
+        let mut namespace_operations = Vec::with_capacity(updates.len());
+        let mut changes = Vec::with_capacity(updates.len());
+        let mut size_changes = Vec::with_capacity(updates.len());
+        // Records and their sizes as left by the preceding updates of the
+        // batch, so that a key written twice gets two versions and two
+        // consecutive change events.
+        let mut pending: BTreeMap<&str, (Option<Record>, Option<u64>)> = BTreeMap::new();
+        let mut last_version = self.last_version(namespace)?;
+        for update in updates {
+            let (key, new_value) = match update {
+                KeyUpdate::Put { key, value } => (key.as_str(), Some(value)),
+                KeyUpdate::Delete { key } => (key.as_str(), None),
+            };
+            if key.is_empty() {
+                return Err(ServiceError::InvalidArgument(
+                    String::from("Error when trying to write batch in namespace '")
+                        + namespace
+                        + "': Key cannot be empty string.",
+                ));
             }
+            let (current, current_size) = match pending.get(key) {
+                Some(stored) => stored.clone(),
+                None => self.current_record(key, namespace)?,
+            };
+            let record = new_value.map(|value| {
+                last_version = next_version(current.as_ref(), last_version);
+                Record::new(value.clone(), last_version)
+            });
+            let encoded = record.as_ref().map(Record::encode);
+            let new_size = record.as_ref().map(|record| record.value.size());
+            pending.insert(key, (record, new_size));
+            size_changes.push(SizeChange {
+                key_len: key.len() as u64,
+                old_size: current_size,
+                new_size,
+            });
+            changes.push((
+                key,
+                current.map(|current| current.value),
+                new_value.cloned(),
+            ));
+
+            let namespace_key = keys::namespace_key(namespace, key.as_bytes());
+            namespace_operations.push(match encoded {
+                Some(encoded) => BatchOperation::Put {
+                    key: namespace_key,
+                    value: encoded,
+                },
+                None => BatchOperation::Delete { key: namespace_key },
+            });
+        }
+        let usage = self.check_quota(namespace, &size_changes)?;
+        if dry_run {
+            return Ok(());
         }
+        namespace_operations.push(version_operation(namespace, last_version));
+        namespace_operations.extend(extra_operations);
+
+        self.backend
+            .write_batch(&namespace_operations)
+            .map_err(|e| {
+                ServiceError::from_facade(
+                    &e,
+                    String::from("Error when trying to write batch of ")
+                        + &updates.len().to_string()
+                        + " operations in namespace '"
+                        + namespace
+                        + "': "
+                        + &e.to_string(),
+                )
+            })?;
+        self.update_usage(namespace, usage);
+        self.versions.insert(namespace.to_string(), last_version);
+        for (key, old_value, new_value) in changes {
+            if old_value.is_some() || new_value.is_some() {
+                self.notify(key, namespace, old_value, new_value);
+            }
+        }
+        Ok(())
     }
 
-    pub fn read_db(&mut self, key: &str, namespace: &str) -> (bool, String, String) {
-        let (is_open, msg) = self.open_db();
-        if !is_open {
-            return (false, msg, String::from(""));
+    /// Returns the sorted keys in `namespace` that contain `substring`.
+    pub fn search_db(
+        &mut self,
+        substring: &str,
+        namespace: &str,
+    ) -> Result<Vec<String>, ServiceError> {
+        self.open_db()?;
+        let keys = self.keys_with_prefix("", namespace).map_err(|e| {
+            ServiceError::from_facade(
+                &e,
+                String::from("Error when trying to search for keys containing '")
+                    + substring
+                    + "' in namespace '"
+                    + namespace
+                    + "': "
+                    + &e.to_string(),
+            )
+        })?;
+        let mut res = utf8_keys(keys)
+            .into_iter()
+            .filter(|string| string.contains(substring))
+            .collect::<Vec<String>>();
+        res.sort();
+        Ok(res)
+    }
+
+    /// Deletes `node` and all keys below it, returning the deleted keys.
+    ///
+    /// The keys are deleted in a single batch, so either the entire subtree is
+    /// deleted or, if that fails, nothing is.
+    pub fn delete_recursively_from_db(
+        &mut self,
+        node: &str,
+        namespace: &str,
+    ) -> Result<Vec<String>, ServiceError> {
+        self.open_db()?;
+        self.check_writable(namespace)?;
+
+        if node.is_empty() {
+            return Err(ServiceError::InvalidArgument(
+                "Error: Key String was empty!".to_string(),
+            ));
         }
-        let namespace_key = format!("{namespace}_.{key}");
-        match self.rocks_db_facade.read_db(namespace_key.as_str()) {
-            Ok(value) => {
-                return (
-                    true,
-                    String::from("Retrieved value '")
-                        + &value
-                        + "' from key '"
-                        + key
+
+        let mut res = self
+            .stored_keys_with_prefix(&(node.to_string() + "."), namespace)
+            .map_err(|e| {
+                ServiceError::from_facade(
+                    &e,
+                    "Error when trying to list keys with prefix '".to_string()
+                        + node
+                        + "': "
+                        + &e.to_string(),
+                )
+            })?;
+        res.push(node.as_bytes().to_vec());
+        let now_ms = now_ms();
+        let mut deleted_keys = Vec::with_capacity(res.len());
+        let mut old_values = Vec::with_capacity(res.len());
+        let mut size_changes = Vec::with_capacity(res.len());
+        let mut operations = Vec::with_capacity(res.len());
+        for key in &res {
+            let namespace_key = keys::namespace_key(namespace, key);
+            let (record, size) = match self.stored_record(&namespace_key).map_err(|e| {
+                ServiceError::from_facade(
+                    &e,
+                    "Error when trying to read key '".to_string()
+                        + &String::from_utf8_lossy(key)
                         + "' in namespace '"
                         + namespace
-                        + "'",
-                    value,
+                        + "': "
+                        + &e.to_string(),
                 )
+            })? {
+                Some(stored) => stored,
+                None => continue,
+            };
+            size_changes.push(SizeChange {
+                key_len: key.len() as u64,
+                old_size: Some(size),
+                new_size: None,
+            });
+            operations.push(BatchOperation::Delete { key: namespace_key });
+            // Expired keys are removed as well, but were already gone for the
+            // clients and are not reported again.
+            if !record.is_expired(now_ms) {
+                old_values.push(Some(record.value));
+                // Keys that are not valid UTF-8 are deleted as well, but can
+                // only be reported lossily.
+                deleted_keys.push(String::from_utf8_lossy(key).into_owned());
             }
-            Err(e) => {
-                return (
-                    false,
-                    String::from("Error when trying to retrieve from key '")
-                        + key
-                        + "' in namespace '"
+        }
+        let usage = self.check_quota(namespace, &size_changes)?;
+        self.backend.write_batch(&operations).map_err(|e| {
+            ServiceError::from_facade(
+                &e,
+                "Error deleting the keys below node '".to_string()
+                    + node
+                    + "' in namespace '"
+                    + namespace
+                    + "': "
+                    + &e.to_string(),
+            )
+        })?;
+        self.update_usage(namespace, usage);
+        for (key, old_value) in deleted_keys.iter().zip(old_values) {
+            self.notify(key, namespace, old_value, None);
+        }
+        Ok(deleted_keys)
+    }
+
+    /// Lists the namespaces that contain keys, sorted by name.
+    pub fn list_namespaces(&mut self) -> Result<Vec<NamespaceInfo>, ServiceError> {
+        self.open_db()?;
+        let namespaces = self.backend.list_namespaces().map_err(|e| {
+            ServiceError::from_facade(
+                &e,
+                String::from("Error when trying to list namespaces: ") + &e.to_string(),
+            )
+        })?;
+        namespaces
+            .iter()
+            .map(|namespace| self.namespace_info(namespace))
+            .collect()
+    }
+
+    /// Returns the number and size of the keys in `namespace`, which are 0 if
+    /// the namespace contains no keys.
+    pub fn namespace_info(&mut self, namespace: &str) -> Result<NamespaceInfo, ServiceError> {
+        self.open_db()?;
+        let usage = self.backend.namespace_usage(namespace).map_err(|e| {
+            ServiceError::from_facade(
+                &e,
+                String::from("Error when trying to count the keys in namespace '")
+                    + namespace
+                    + "': "
+                    + &e.to_string(),
+            )
+        })?;
+        Ok(NamespaceInfo {
+            namespace: namespace.to_string(),
+            key_count: usage.keys,
+            size_bytes: usage.bytes,
+            read_only: self
+                .namespaces
+                .get(namespace)
+                .is_some_and(|policy| policy.read_only),
+        })
+    }
+
+    /// Deletes all keys in `namespace` at once: either all of them are deleted
+    /// or, if that fails, none is.
+    pub fn drop_namespace(&mut self, namespace: &str) -> Result<(), ServiceError> {
+        self.open_db()?;
+        self.check_writable(namespace)?;
+
+        // The keys are only listed for the change events, so that dropping a
+        // namespace nobody watches does not read it first.
+        let mut old_values = Vec::new();
+        if self.events.receiver_count() > 0 {
+            let keys = self.keys_with_prefix("", namespace).map_err(|e| {
+                ServiceError::from_facade(
+                    &e,
+                    String::from("Error when trying to list the keys in namespace '")
                         + namespace
                         + "': "
                         + &e.to_string(),
-                    String::from(""),
                 )
+            })?;
+            for key in utf8_keys(keys) {
+                let old_value =
+                    self.previous_value(&keys::namespace_key(namespace, key.as_bytes()))?;
+                old_values.push((key, old_value));
             }
         }
+        self.backend.drop_namespace(namespace).map_err(|e| {
+            ServiceError::from_facade(
+                &e,
+                String::from("Error when trying to drop namespace '")
+                    + namespace
+                    + "': "
+                    + &e.to_string(),
+            )
+        })?;
+        let markers = self
+            .backend
+            .list_keys_with_prefix(&keys::seeded_marker_prefix(namespace))
+            .and_then(|markers| {
+                let operations: Vec<_> = markers
+                    .into_iter()
+                    .map(|key| BatchOperation::Delete { key })
+                    .collect();
+                self.backend.write_batch(&operations)
+            });
+        if let Err(e) = markers {
+            return Err(ServiceError::from_facade(
+                &e,
+                String::from("Error when trying to reset the defaults of namespace '")
+                    + namespace
+                    + "': "
+                    + &e.to_string(),
+            ));
+        }
+        if self.tracks_usage(namespace) {
+            self.usage
+                .insert(namespace.to_string(), NamespaceUsage::default());
+        }
+        for (key, old_value) in old_values {
+            self.notify(&key, namespace, old_value, None);
+        }
+        Ok(())
     }
 
-    pub fn check_if_key_exists(&mut self, key: &str, namespace: &str) -> bool {
-        let namespace_key = format!("{namespace}_.{key}");
-        match self.rocks_db_facade.read_db(namespace_key.as_str()) {
-            Ok(_value) => return true,
-            Err(_e) => return false,
+    /// Removes the expired keys of all namespaces from the database and returns
+    /// their number, see `expiry_scans`.
+    pub fn remove_expired(&mut self) -> Result<usize, ServiceError> {
+        let mut removed = 0;
+        for mut scan in self.expiry_scans()? {
+            removed += self.remove_expired_keys(&mut scan, usize::MAX)?;
+            self.finish_expiry_scan(scan)?;
         }
+        Ok(removed)
     }
 
-    pub fn delete_db(&mut self, key: &str, namespace: &str) -> (bool, String) {
-        let (is_open, msg) = self.open_db();
-        if !is_open {
-            return (false, msg);
+    /// Lists the keys of the namespaces that may hold keys that expire, for
+    /// `remove_expired_keys` to remove the expired ones.
+    ///
+    /// Expired keys are already treated as deleted when they expire, so this
+    /// only frees their space. Only the keys are listed, so that the records can
+    /// be checked in chunks, each of which holds up other requests only
+    /// briefly.
+    pub fn expiry_scans(&mut self) -> Result<Vec<ExpiryScan>, ServiceError> {
+        self.open_db()?;
+        let map_error = |e: FacadeError| {
+            ServiceError::from_facade(
+                &e,
+                String::from("Error when trying to list keys that expire: ") + &e.to_string(),
+            )
+        };
+        let markers = self
+            .backend
+            .list_keys_with_prefix(keys::EXPIRY_MARKER_PREFIX)
+            .map_err(map_error)?;
+        let mut scans = Vec::new();
+        for marker in markers {
+            let namespace = match keys::split_expiry_marker_key(&marker) {
+                Some(namespace) => namespace.to_string(),
+                None => continue,
+            };
+            let keys = self
+                .backend
+                .list_keys_with_prefix(&keys::namespace_prefix(&namespace))
+                .map_err(map_error)?;
+            let ttl_writes = self.ttl_writes.get(&namespace).copied().unwrap_or(0);
+            scans.push(ExpiryScan {
+                namespace,
+                keys,
+                ttl_writes,
+                expiring_left: false,
+            });
         }
+        Ok(scans)
+    }
 
-        if self.check_if_key_exists(key, namespace) {
-            let namespace_key = format!("{namespace}_.{key}");
-            match self.rocks_db_facade.delete_db(&namespace_key.as_str()) {
-                Ok(()) => {
-                    return (
-                        true,
-                        String::from("Deleted key '") + key + "' in namespace '" + namespace + "'",
-                    )
+    /// Checks the next `limit` keys of `scan`, removes the expired ones in a
+    /// single batch and returns their number.
+    ///
+    /// Watchers still get a delete event for each removed key, as that is when
+    /// they learn that it expired.
+    pub fn remove_expired_keys(
+        &mut self,
+        scan: &mut ExpiryScan,
+        limit: usize,
+    ) -> Result<usize, ServiceError> {
+        self.open_db()?;
+        let map_error = |e: FacadeError| {
+            ServiceError::from_facade(
+                &e,
+                String::from("Error when trying to remove expired keys: ") + &e.to_string(),
+            )
+        };
+        let now_ms = now_ms();
+        let mut operations = Vec::new();
+        let mut expired = Vec::new();
+        let count = limit.min(scan.keys.len());
+        for namespace_key in scan.keys.drain(..count) {
+            let key = match keys::split_namespace_key(&namespace_key) {
+                Some((_, key)) => key.to_vec(),
+                None => continue,
+            };
+            match self.stored_record(&namespace_key).map_err(map_error)? {
+                Some((record, size)) if record.is_expired(now_ms) => {
+                    expired.push((key, record.value, size));
+                    operations.push(BatchOperation::Delete { key: namespace_key });
                 }
-                Err(e) => {
-                    return (
-                        false,
-                        String::from("Error when trying to delete key '")
-                            + key
-                            + "' in namespace '"
-                            + namespace
-                            + "': "
-                            + &e.to_string(),
-                    )
+                Some((record, _)) if record.expires_ms.is_some() => scan.expiring_left = true,
+                _ => {}
+            }
+        }
+        if operations.is_empty() {
+            return Ok(0);
+        }
+        self.backend.write_batch(&operations).map_err(map_error)?;
+        let removed = expired.len();
+        for (key, value, size) in expired {
+            if let Some(usage) = self.usage.get_mut(&scan.namespace) {
+                SizeChange {
+                    key_len: key.len() as u64,
+                    old_size: Some(size),
+                    new_size: None,
                 }
+                .apply_to(usage);
             }
-        } else {
-            return (
-                false,
-                String::from("Key '") + key + "' does not exist in namespace '" + namespace + "'!",
+            self.notify(
+                &String::from_utf8_lossy(&key),
+                &scan.namespace,
+                Some(value),
+                None,
             );
         }
+        Ok(removed)
     }
 
-    pub fn search_db(&mut self, substring: &str, namespace: &str) -> (bool, String, Vec<String>) {
-        let (is_open, msg) = self.open_db();
-        if !is_open {
-            return (false, msg, Vec::new());
+    /// Ends `scan`. If it checked all keys of its namespace without finding one
+    /// that expires later, and nothing was written with a time to live there
+    /// meanwhile, the namespace is no longer marked as holding keys that
+    /// expire.
+    pub fn finish_expiry_scan(&mut self, scan: ExpiryScan) -> Result<(), ServiceError> {
+        let ttl_writes = self.ttl_writes.get(&scan.namespace).copied().unwrap_or(0);
+        if !scan.is_done() || scan.expiring_left || ttl_writes != scan.ttl_writes {
+            return Ok(());
         }
-        let namespace_prefix = format!("{namespace}_.");
+        self.open_db()?;
         match self
-            .rocks_db_facade
-            .list_keys_with_prefix(namespace_prefix.as_str())
+            .backend
+            .delete_db(&keys::expiry_marker_key(&scan.namespace))
         {
-            Ok(value) => {
-                let mut res = value
-                    .into_iter()
-                    .filter(|string| string.contains(substring))
-                    .map(|string| {
-                        string
-                            .strip_prefix(namespace_prefix.as_str())
-                            .expect("nothing left after stripping prefix")
-                            .to_owned()
-                    })
-                    .collect::<Vec<String>>();
-
-                res.sort();
-                return (
-                    true,
-                    String::from("Retrieved list of keys containing substring '")
-                        + substring
-                        + "' in namespace '"
-                        + namespace
-                        + "'",
-                    res,
-                );
-            }
+            Ok(()) | Err(FacadeError::NotFound) => {}
             Err(e) => {
-                return (
-                    false,
-                    String::from("Error when trying to search for keys containing '")
-                        + substring
-                        + "' in namespace '"
-                        + namespace
+                return Err(ServiceError::from_facade(
+                    &e,
+                    String::from("Error when trying to clear the expiry marker of namespace '")
+                        + &scan.namespace
                         + "': "
                         + &e.to_string(),
-                    Vec::new(),
-                )
+                ))
             }
         }
+        self.expiring.insert(scan.namespace, false);
+        Ok(())
     }
 
-    pub fn delete_recursively_from_db(&mut self, node: &str, namespace: &str) -> (bool, String) {
-        let (is_open, msg) = self.open_db();
-        if !is_open {
-            return (false, msg);
+    /// Returns `node` and all keys below it, or the entire namespace if `node`
+    /// is empty, as nested JSON, see `tree::to_tree`.
+    ///
+    /// The keys keep their full names, so the tree starts at the top-level
+    /// node of `node`. Fails with `ServiceError::NotFound` if `node` is not
+    /// empty and neither a key nor the parent of one.
+    pub fn export(
+        &mut self,
+        node: &str,
+        namespace: &str,
+    ) -> Result<serde_json::Value, ServiceError> {
+        self.open_db()?;
+        let prefix = if node.is_empty() {
+            String::new()
+        } else {
+            node.to_string() + "."
+        };
+        let mut keys = self.keys_with_prefix(&prefix, namespace).map_err(|e| {
+            ServiceError::from_facade(
+                &e,
+                String::from("Error when trying to export node '")
+                    + node
+                    + "' in namespace '"
+                    + namespace
+                    + "': "
+                    + &e.to_string(),
+            )
+        })?;
+        if !node.is_empty() {
+            keys.push(node.as_bytes().to_vec());
         }
-
-        if node.is_empty() {
-            return (false, "Error: Key String was empty!".to_string());
+        let mut entries = Vec::with_capacity(keys.len());
+        for key in utf8_keys(keys) {
+            match self.load_record(&keys::namespace_key(namespace, key.as_bytes())) {
+                Ok(record) => entries.push((key, record.value)),
+                Err(FacadeError::NotFound) => {}
+                Err(e) => {
+                    return Err(ServiceError::from_facade(
+                        &e,
+                        String::from("Error when trying to export key '")
+                            + &key
+                            + "' in namespace '"
+                            + namespace
+                            + "': "
+                            + &e.to_string(),
+                    ))
+                }
+            }
         }
+        if entries.is_empty() && !node.is_empty() {
+            return Err(ServiceError::NotFound(
+                String::from("Error when trying to export node '")
+                    + node
+                    + "': node '"
+                    + node
+                    + "' doesn't exist in namespace '"
+                    + namespace
+                    + "'",
+            ));
+        }
+        tree::to_tree(entries).map_err(|message| {
+            ServiceError::FailedPrecondition(
+                String::from("Error when trying to export node '")
+                    + node
+                    + "' in namespace '"
+                    + namespace
+                    + "': "
+                    + &message,
+            )
+        })
+    }
 
-        let mut deleted_keys = "Deleted Keys: ".to_string();
-
-        let namespace_node = format!("{namespace}_.{node}.");
-        match self
-            .rocks_db_facade
-            .list_keys_with_prefix(namespace_node.as_str())
+    /// Writes the keys of a JSON document in the form returned by `export`, or
+    /// a flat object of keys and values, into `namespace` atomically.
+    ///
+    /// If `node` is not empty, all keys of the document must be `node` or
+    /// below it, and `ImportMode::ReplaceSubtree` replaces only that subtree.
+    /// Keys that have the given value already are not written again. With
+    /// `dry_run`, nothing is changed, but the report and all checks are the
+    /// same.
+    pub fn import(
+        &mut self,
+        document: &serde_json::Value,
+        node: &str,
+        mode: ImportMode,
+        dry_run: bool,
+        namespace: &str,
+    ) -> Result<ImportReport, ServiceError> {
+        self.open_db()?;
+        self.check_writable(namespace)?;
+        let entries = tree::from_tree(document).map_err(|message| {
+            ServiceError::InvalidArgument(
+                String::from("Error when trying to import into namespace '")
+                    + namespace
+                    + "': "
+                    + &message,
+            )
+        })?;
+        let node_dot = node.to_string() + ".";
+        if let Some(key) = entries
+            .keys()
+            .find(|key| !node.is_empty() && *key != node && !key.starts_with(&node_dot))
         {
-            Ok(mut res) => {
-                if self.check_if_key_exists(node, namespace) {
-                    res.push(format!("{namespace}_.{node}"));
+            return Err(ServiceError::InvalidArgument(
+                String::from("Error when trying to import into node '")
+                    + node
+                    + "': key '"
+                    + key
+                    + "' is outside of the node",
+            ));
+        }
+
+        let mut report = ImportReport::default();
+        let mut updates = Vec::new();
+        if mode == ImportMode::ReplaceSubtree {
+            let prefix = if node.is_empty() { "" } else { &node_dot };
+            let mut existing = self.keys_with_prefix(prefix, namespace).map_err(|e| {
+                ServiceError::from_facade(
+                    &e,
+                    String::from("Error when trying to list the keys below node '")
+                        + node
+                        + "' in namespace '"
+                        + namespace
+                        + "': "
+                        + &e.to_string(),
+                )
+            })?;
+            if !node.is_empty() && self.check_if_key_exists(node, namespace)? {
+                existing.push(node.as_bytes().to_vec());
+            }
+            for key in utf8_keys(existing) {
+                if !entries.contains_key(&key) {
+                    updates.push(KeyUpdate::Delete { key: key.clone() });
+                    report.deleted.push(key);
                 }
-                for mut key in res {
-                    match self.rocks_db_facade.delete_db(&key.as_str()) {
-                        Ok(()) => {
-                            let namespace_prefix = format!("{namespace}_.");
-                            key = key
-                                .strip_prefix(namespace_prefix.as_str())
-                                .expect("nothing left after stripping prefix")
-                                .to_owned();
-                            deleted_keys = format!("{} {}", deleted_keys, key);
-                        }
-                        Err(_e) => {
-                            return (
-                                false,
-                                "Error deleting key '".to_string()
-                                    + &key
-                                    + "' in namespace '"
-                                    + namespace
-                                    + "'.",
-                            )
-                        }
-                    }
+            }
+        }
+        for (key, value) in entries {
+            let (current, _) = self.current_record(&key, namespace)?;
+            match current {
+                Some(current) if mode == ImportMode::FillMissing || current.value == value => {
+                    report.unchanged.push(key);
                 }
-                return (
-                    true,
-                    "Successfully deleted keys: ".to_string()
-                        + &deleted_keys
-                        + " in namespace '"
+                Some(_) => {
+                    report.updated.push(key.clone());
+                    updates.push(KeyUpdate::Put { key, value });
+                }
+                None => {
+                    report.created.push(key.clone());
+                    updates.push(KeyUpdate::Put { key, value });
+                }
+            }
+        }
+        report.deleted.sort();
+        self.apply_updates(&updates, namespace, dry_run, Vec::new())?;
+        Ok(report)
+    }
+
+    /// Writes the keys of `defaults` that do not exist in `namespace`
+    /// atomically and returns them, or `None` if the namespace is read-only and
+    /// `defaults.write_read_only` is not configured.
+    ///
+    /// Existing keys keep their values. Each key of `defaults` is marked as
+    /// seeded, so that it does not get its default again with the next start
+    /// once it was deleted, until DestroyDB or DropNamespace resets it. Keys
+    /// added to `defaults` later still get theirs.
+    pub fn seed_defaults(
+        &mut self,
+        defaults: &BTreeMap<String, Value>,
+        namespace: &str,
+    ) -> Result<Option<Vec<String>>, ServiceError> {
+        self.open_db()?;
+        if !self.seed_read_only && self.check_writable(namespace).is_err() {
+            return Ok(None);
+        }
+        let marked: BTreeSet<Vec<u8>> = self
+            .backend
+            .list_keys_with_prefix(&keys::seeded_marker_prefix(namespace))
+            .map_err(|e| {
+                ServiceError::from_facade(
+                    &e,
+                    String::from("Error when trying to read the defaults of namespace '")
                         + namespace
-                        + "'.",
-                );
+                        + "': "
+                        + &e.to_string(),
+                )
+            })?
+            .iter()
+            .filter_map(|marker| keys::split_seeded_marker_key(marker).map(<[u8]>::to_vec))
+            .collect();
+        let mut seeded = Vec::new();
+        let mut updates = Vec::new();
+        let mut markers = Vec::new();
+        for (key, value) in defaults {
+            if marked.contains(key.as_bytes()) {
+                continue;
             }
-            Err(_e) => (
-                false,
-                "Error when trying to list keys with prefix '".to_string() + node + "'",
-            ),
+            if let (None, _) = self.current_record(key, namespace)? {
+                seeded.push(key.clone());
+                updates.push(KeyUpdate::Put {
+                    key: key.clone(),
+                    value: value.clone(),
+                });
+            }
+            markers.push(BatchOperation::Put {
+                key: keys::seeded_marker_key(namespace, key.as_bytes()),
+                value: Vec::new(),
+            });
+        }
+        if !markers.is_empty() {
+            self.apply_updates(&updates, namespace, false, markers)?;
         }
+        Ok(Some(seeded))
     }
 
+    /// Lists the nodes `layers` levels below `node`, or all keys below it if `layers` is 0.
     pub fn nodes_starting_in(
         &mut self,
         node: &str,
         layers: Option<i32>,
         namespace: &str,
-    ) -> (bool, String, Vec<String>) {
+    ) -> Result<Vec<String>, ServiceError> {
         let l = layers.unwrap_or(1);
         if l < 0 {
-            return (
-                false,
+            return Err(ServiceError::InvalidArgument(
                 String::from("Error when trying to list nodes starting in '")
                     + node
                     + "' exactly "
                     + &l.to_string()
                     + " layers deep: layers must be non-negative",
-                Vec::new(),
-            );
-        }
-        let (is_open, msg) = self.open_db();
-        if !is_open {
-            return (false, msg, Vec::new());
+            ));
         }
+        self.open_db()?;
         let mut node_dot = String::from(node);
         if !node.is_empty() {
             node_dot.push('.');
         }
-        let namespace_node_dot = format!("{namespace}_.{node_dot}");
-        let namespace_prefix = format!("{namespace}_.");
-        match self
-            .rocks_db_facade
-            .list_keys_with_prefix(&namespace_node_dot)
-        {
-            Ok(mut value) => {
-                if l == 0 {
-                    if self.check_if_key_exists(node, namespace) {
-                        value.push(format!("{namespace}_.{node}"));
-                    }
-                    if value.is_empty() && !node.is_empty() {
-                        return (
-                            false,
-                            String::from("Error when trying to list nodes starting in '")
-                                + node
-                                + "' exactly "
-                                + &l.to_string()
-                                + " layers deep: node '"
-                                + node
-                                + "' doesn't exist",
-                            Vec::new(),
-                        );
-                    }
-                    value = value
-                        .into_iter()
-                        .map(|string| {
-                            string
-                                .strip_prefix(namespace_prefix.as_str())
-                                .expect("nothing left after stripping prefix")
-                                .to_owned()
-                        })
-                        .collect::<Vec<String>>();
-                    value.sort();
-                    return (
-                        true,
-                        String::from("Retrieved list of keys starting in '")
-                            + node
-                            + "' any number of layers deep (special case layers = '0')",
-                        value,
-                    );
-                } else {
-                    if value.is_empty()
-                        && !node.is_empty()
-                        && !self.check_if_key_exists(node, namespace)
-                    {
-                        return (
-                            false,
-                            String::from("Error when trying to list nodes starting in '")
-                                + node
-                                + "' exactly "
-                                + &l.to_string()
-                                + " layers deep: node '"
-                                + node
-                                + "' doesn't exist",
-                            Vec::new(),
-                        );
-                    }
-                    let total_depth: i32 =
-                        namespace_node_dot.chars().filter(|&c| c == '.').count() as i32 - 1 + l;
-                    let mut res: Vec<String> = Vec::new();
-                    for key in value.iter_mut() {
-                        let mut count = 0;
-                        for (i, c) in key.chars().enumerate() {
-                            if c == '.' {
-                                count += 1;
-                                if count > total_depth {
-                                    res.push(key[..i].to_string());
-                                    break;
-                                }
-                            }
-                        }
-                        if count == total_depth {
-                            res.push(key.to_string());
+        let node_not_found = || {
+            ServiceError::NotFound(
+                String::from("Error when trying to list nodes starting in '")
+                    + node
+                    + "' exactly "
+                    + &l.to_string()
+                    + " layers deep: node '"
+                    + node
+                    + "' doesn't exist",
+            )
+        };
+        let keys = self.keys_with_prefix(&node_dot, namespace).map_err(|e| {
+            ServiceError::from_facade(
+                &e,
+                String::from("Error when trying to list nodes starting in '")
+                    + node
+                    + "' exactly "
+                    + &l.to_string()
+                    + " layers deep: "
+                    + &e.to_string(),
+            )
+        })?;
+        let mut value = utf8_keys(keys);
+        if l == 0 {
+            if self.check_if_key_exists(node, namespace)? {
+                value.push(node.to_string());
+            }
+            if value.is_empty() && !node.is_empty() {
+                return Err(node_not_found());
+            }
+            value.sort();
+            Ok(value)
+        } else {
+            if value.is_empty() && !node.is_empty() && !self.check_if_key_exists(node, namespace)? {
+                return Err(node_not_found());
+            }
+            let total_depth: i32 = node_dot.chars().filter(|&c| c == '.').count() as i32 - 1 + l;
+            let mut res: Vec<String> = Vec::new();
+            for key in value.iter_mut() {
+                let mut count = 0;
+                for (i, c) in key.chars().enumerate() {
+                    if c == '.' {
+                        count += 1;
+                        if count > total_depth {
+                            res.push(key[..i].to_string());
+                            break;
                         }
                     }
-                    res = res
-                        .into_iter()
-                        .map(|string| {
-                            string
-                                .strip_prefix(namespace_prefix.as_str())
-                                .expect("nothing left after stripping prefix")
-                                .to_owned()
-                        })
-                        .collect::<Vec<String>>();
-                    res.sort();
-                    res.dedup();
-                    return (
-                        true,
-                        String::from("Retrieved list of nodes starting in '")
-                            + node
-                            + "' exactly "
-                            + &l.to_string()
-                            + " layers deep",
-                        res,
-                    );
+                }
+                if count == total_depth {
+                    res.push(key.to_string());
                 }
             }
+            res.sort();
+            res.dedup();
+            Ok(res)
+        }
+    }
+}
+
+/// Converts keys to strings. Keys that are not valid UTF-8 are
+/// skipped with a warning, so that they do not break listing the other keys.
+fn utf8_keys(keys: Vec<Vec<u8>>) -> Vec<String> {
+    keys.into_iter()
+        .filter_map(|key| match String::from_utf8(key) {
+            Ok(key) => Some(key),
             Err(e) => {
-                return (
-                    false,
-                    String::from("Error when trying to list nodes starting in '")
-                        + node
-                        + "' exactly "
-                        + &l.to_string()
-                        + " layers deep: "
-                        + &e.to_string(),
-                    Vec::new(),
-                )
+                log::warn!(
+                    "Skipping key that is not valid UTF-8: '{}'",
+                    String::from_utf8_lossy(e.as_bytes())
+                );
+                None
+            }
+        })
+        .collect()
+}
+
+/// Returns the version of the next write of a key with the record `current`,
+/// given the last version given out in its namespace. Records written before
+/// the namespace had a counter may have a higher version than it.
+fn next_version(current: Option<&Record>, last_version: u64) -> u64 {
+    current
+        .map_or(0, |current| current.version)
+        .max(last_version)
+        + 1
+}
+
+/// Returns the operation that stores `version` as the last version given out
+/// in `namespace`.
+fn version_operation(namespace: &str, version: u64) -> BatchOperation {
+    BatchOperation::Put {
+        key: keys::version_key(namespace),
+        value: version.to_be_bytes().to_vec(),
+    }
+}
+
+/// Returns `value` as text for the string-based APIs, or fails with
+/// `ServiceError::FailedPrecondition` if it has no text form.
+pub fn text_value(key: &str, namespace: &str, value: &Value) -> Result<String, ServiceError> {
+    value.as_text().ok_or_else(|| {
+        ServiceError::FailedPrecondition(
+            String::from("Key '")
+                + key
+                + "' in namespace '"
+                + namespace
+                + "' holds a value of type "
+                + &value.value_type().to_string()
+                + ", which cannot be read as string",
+        )
+    })
+}
+
+/// Fails with `ServiceError::Aborted` if `expected_version` is given and differs
+/// from the version of `current`, where a missing key has version 0.
+fn check_version(
+    key: &str,
+    namespace: &str,
+    current: Option<&Record>,
+    expected_version: Option<u64>,
+) -> Result<(), ServiceError> {
+    let current_version = current.map_or(0, |current| current.version);
+    match expected_version {
+        Some(expected_version) if expected_version != current_version => {
+            Err(ServiceError::Aborted(
+                String::from("Key '")
+                    + key
+                    + "' in namespace '"
+                    + namespace
+                    + "' has version "
+                    + &current_version.to_string()
+                    + ", but version "
+                    + &expected_version.to_string()
+                    + " was expected",
+            ))
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::DefaultsConfig;
+    use crate::memory::MemoryFacade;
+    use crate::service::*;
+
+    #[test]
+    fn check_if_key_exists_test() {
+        let mut service = DbService::new(Box::new(MemoryFacade::new()), &Config::default());
+
+        let result_closed = service.check_if_key_exists("a", "");
+        service.write_db("a", "a", None, "").unwrap();
+
+        assert!(matches!(result_closed, Err(ServiceError::Unavailable(_))));
+        assert_eq!(service.check_if_key_exists("a", ""), Ok(true));
+        assert_eq!(service.check_if_key_exists("b", ""), Ok(false));
+        assert_eq!(service.check_if_key_exists("a", "other"), Ok(false));
+    }
+
+    #[test]
+    fn change_events_test() {
+        let mut service = DbService::new(Box::new(MemoryFacade::new()), &Config::default());
+        service.write_db("Vehicle.Speed", "10", None, "").unwrap();
+        let mut events = service.subscribe();
+
+        service.write_db("Vehicle.Speed", "20", None, "").unwrap();
+        service
+            .write_db("Vehicle.Speed.Unit", "km/h", None, "")
+            .unwrap();
+        service.delete_db("Vehicle.Speed.Unit", None, "").unwrap();
+        service.delete_recursively_from_db("Vehicle", "").unwrap();
+        let _ = service.read_db("Vehicle.Speed", "");
+
+        let event = |key: &str, old_value: Option<&str>, new_value: Option<&str>| ChangeEvent {
+            namespace: String::new(),
+            key: key.to_string(),
+            old_value: old_value.map(Value::from),
+            new_value: new_value.map(Value::from),
+        };
+        assert_eq!(
+            events.try_recv().unwrap(),
+            event("Vehicle.Speed", Some("10"), Some("20"))
+        );
+        assert_eq!(
+            events.try_recv().unwrap(),
+            event("Vehicle.Speed.Unit", None, Some("km/h"))
+        );
+        assert_eq!(
+            events.try_recv().unwrap(),
+            event("Vehicle.Speed.Unit", Some("km/h"), None)
+        );
+        assert_eq!(
+            events.try_recv().unwrap(),
+            event("Vehicle.Speed", Some("20"), None)
+        );
+        assert!(events.try_recv().is_err());
+    }
+
+    #[test]
+    fn delete_recursively_from_db_test() {
+        let mut service = DbService::new(Box::new(MemoryFacade::new()), &Config::default());
+        for key in [
+            "Vehicle",
+            "Vehicle.Speed",
+            "Vehicle.Cabin.Light",
+            "VehicleSpeed",
+        ] {
+            service.write_db(key, "1", None, "").unwrap();
+        }
+
+        let deleted_keys = service.delete_recursively_from_db("Vehicle", "").unwrap();
+
+        assert_eq!(
+            deleted_keys,
+            vec!["Vehicle.Cabin.Light", "Vehicle.Speed", "Vehicle"]
+        );
+        assert_eq!(
+            service.search_db("", ""),
+            Ok(vec!["VehicleSpeed".to_string()])
+        );
+    }
+
+    #[test]
+    fn non_utf8_keys_test() {
+        let mut service = DbService::new(Box::new(MemoryFacade::new()), &Config::default());
+        service.write_db("Vehicle.Speed", "10", None, "").unwrap();
+        service
+            .write_db("Vehicle.Cabin.Light", "on", None, "")
+            .unwrap();
+        service
+            .backend
+            .write_db(&keys::namespace_key("", b"Vehicle.\xFFSpeed"), b"\xC3")
+            .unwrap();
+
+        let result_search = service.search_db("Vehicle", "");
+        let result_nodes = service.nodes_starting_in("Vehicle", Some(1), "");
+        let result_deleted = service.delete_recursively_from_db("Vehicle", "");
+
+        assert_eq!(
+            result_search,
+            Ok(vec![
+                "Vehicle.Cabin.Light".to_string(),
+                "Vehicle.Speed".to_string()
+            ])
+        );
+        assert_eq!(
+            result_nodes,
+            Ok(vec![
+                "Vehicle.Cabin".to_string(),
+                "Vehicle.Speed".to_string()
+            ])
+        );
+        assert_eq!(result_deleted.unwrap().len(), 3);
+        assert!(service.keys_with_prefix("", "").unwrap().is_empty());
+    }
+
+    #[test]
+    fn namespace_isolation_test() {
+        let mut service = DbService::new(Box::new(MemoryFacade::new()), &Config::default());
+        service
+            .write_db("c", "nested namespace", None, "a_.b")
+            .unwrap();
+        service.write_db("b_.c", "nested key", None, "a").unwrap();
+        service.write_db("Speed", "10", None, "a_.x").unwrap();
+
+        let result_nested_namespace = service.read_db("c", "a_.b");
+        let result_nested_key = service.read_db("b_.c", "a");
+        let result_search = service.search_db("", "a");
+        let result_nodes = service.nodes_starting_in("", Some(0), "a");
+        let result_deleted = service.delete_recursively_from_db("b_", "a");
+
+        assert_eq!(result_nested_namespace.unwrap(), "nested namespace");
+        assert_eq!(result_nested_key.unwrap(), "nested key");
+        assert_eq!(result_search, Ok(vec!["b_.c".to_string()]));
+        assert_eq!(result_nodes, Ok(vec!["b_.c".to_string()]));
+        assert_eq!(result_deleted, Ok(vec!["b_.c".to_string()]));
+        assert_eq!(service.read_db("c", "a_.b").unwrap(), "nested namespace");
+        assert_eq!(service.read_db("Speed", "a_.x").unwrap(), "10");
+    }
+
+    #[test]
+    fn migrate_on_open_test() {
+        let mut backend = MemoryFacade::new();
+        backend.open_db("").unwrap();
+        backend.write_db(b"_.Vehicle.Speed", b"10").unwrap();
+        backend.write_db(b"AppName_.Private.Info", b"x").unwrap();
+        let mut service = DbService::new(Box::new(backend), &Config::default());
+
+        let result_default = service.read_db("Vehicle.Speed", "");
+        let result_app = service.search_db("", "AppName");
+
+        assert_eq!(result_default.unwrap(), "10");
+        assert_eq!(result_app, Ok(vec!["Private.Info".to_string()]));
+        assert_eq!(
+            service.search_db("", ""),
+            Ok(vec!["Vehicle.Speed".to_string()])
+        );
+    }
+
+    #[test]
+    fn namespaces_test() {
+        let config = Config {
+            namespaces: BTreeMap::from([(
+                String::from("vehicle-config"),
+                NamespacePolicy {
+                    read_only: true,
+                    ..NamespacePolicy::default()
+                },
+            )]),
+            ..Config::default()
+        };
+        let mut service = DbService::new(Box::new(MemoryFacade::new()), &config);
+        service.write_db("Vehicle.Speed", "10", None, "").unwrap();
+        service
+            .write_db("Private.Info", "test", None, "AppName")
+            .unwrap();
+        service
+            .write_db("Private.Id", "12", None, "AppName")
+            .unwrap();
+        let mut events = service.subscribe();
+
+        let result_list = service.list_namespaces();
+        let result_info = service.namespace_info("vehicle-config");
+        let result_read_only = service.drop_namespace("vehicle-config");
+        let result_drop = service.drop_namespace("AppName");
+
+        assert_eq!(
+            result_list,
+            Ok(vec![
+                NamespaceInfo {
+                    namespace: String::new(),
+                    key_count: 1,
+                    size_bytes: 13 + Record::new(Value::from("10"), 1).encode().len() as u64,
+                    read_only: false,
+                },
+                NamespaceInfo {
+                    namespace: String::from("AppName"),
+                    key_count: 2,
+                    size_bytes: 22
+                        + Record::new(Value::from("test"), 1).encode().len() as u64
+                        + Record::new(Value::from("12"), 1).encode().len() as u64,
+                    read_only: false,
+                },
+            ])
+        );
+        assert_eq!(
+            result_info,
+            Ok(NamespaceInfo {
+                namespace: String::from("vehicle-config"),
+                key_count: 0,
+                size_bytes: 0,
+                read_only: true,
+            })
+        );
+        assert!(matches!(
+            result_read_only,
+            Err(ServiceError::PermissionDenied(_))
+        ));
+        assert_eq!(result_drop, Ok(()));
+        assert_eq!(service.search_db("", "AppName"), Ok(vec![]));
+        assert_eq!(service.read_db("Vehicle.Speed", "").unwrap(), "10");
+        let event = events.try_recv().unwrap();
+        assert_eq!(event.key, "Private.Id");
+        assert_eq!(event.new_value, None);
+        assert_eq!(events.try_recv().unwrap().key, "Private.Info");
+    }
+
+    #[test]
+    fn quota_test() {
+        let config = Config {
+            namespaces: BTreeMap::from([(
+                String::from("media"),
+                NamespacePolicy {
+                    max_keys: Some(2),
+                    max_bytes: Some(33),
+                    max_value_size: Some(30),
+                    max_key_length: Some(10),
+                    ..NamespacePolicy::default()
+                },
+            )]),
+            ..Config::default()
+        };
+        let mut service = DbService::new(Box::new(MemoryFacade::new()), &config);
+        service.write_db("a", "1", None, "media").unwrap();
+        service.write_db("b", "2", None, "media").unwrap();
+
+        let result_keys = service.write_db("c", "3", None, "media");
+        let result_overwrite = service.write_db("a", "11", None, "media");
+        let result_key_length = service.write_db("Vehicle.Speed", "1", None, "media");
+        let result_value_size = service.write_db("a", &"x".repeat(31), None, "media");
+        let result_value_size_ttl = service.write_with_ttl(
+            "a",
+            Value::String("x".repeat(30)),
+            None,
+            Some(60_000),
+            "media",
+        );
+        let result_batch = service.write_batch(
+            &[
+                KeyUpdate::Delete {
+                    key: "a".to_string(),
+                },
+                KeyUpdate::Put {
+                    key: "c".to_string(),
+                    value: Value::from("3"),
+                },
+            ],
+            "media",
+        );
+        service.delete_recursively_from_db("b", "media").unwrap();
+        let result_bytes = service.write_db("d", &"x".repeat(11), None, "media");
+        let result_bytes_full = service.write_value("d", Value::Bytes(vec![0; 30]), None, "media");
+        let result_bytes_exceeded = service.write_db("c", "33", None, "media");
+        let result_other_namespace = service.write_db("Vehicle.Speed", "1", None, "");
+
+        assert!(matches!(
+            result_keys,
+            Err(ServiceError::ResourceExhausted(_))
+        ));
+        assert!(result_overwrite.is_ok());
+        assert!(matches!(
+            result_key_length,
+            Err(ServiceError::ResourceExhausted(_))
+        ));
+        assert!(matches!(
+            result_value_size,
+            Err(ServiceError::ResourceExhausted(_))
+        ));
+        assert!(result_value_size_ttl.is_ok());
+        assert_eq!(result_batch, Ok(()));
+        assert!(result_bytes.is_ok());
+        assert!(result_bytes_full.is_ok());
+        assert!(matches!(
+            result_bytes_exceeded,
+            Err(ServiceError::ResourceExhausted(_))
+        ));
+        assert!(result_other_namespace.is_ok());
+        assert_eq!(
+            service.usage["media"],
+            NamespaceUsage {
+                keys: 2,
+                bytes: (1 + 1) + (1 + 30),
             }
+        );
+        assert_eq!(
+            service.count_usage("media").unwrap(),
+            service.usage["media"]
+        );
+        assert!(matches!(
+            service.write_db("e", "1", None, "media"),
+            Err(ServiceError::ResourceExhausted(_))
+        ));
+    }
+
+    #[test]
+    fn versions_test() {
+        let mut service = DbService::new(Box::new(MemoryFacade::new()), &Config::default());
+
+        let result_create = service.write_db("Vehicle.Speed", "10", Some(0), "");
+        let result_update = service.write_db("Vehicle.Speed", "20", Some(1), "");
+        let result_stale_write = service.write_db("Vehicle.Speed", "30", Some(1), "");
+        let result_stale_delete = service.delete_db("Vehicle.Speed", Some(1), "");
+        let record = service.read_record("Vehicle.Speed", "").unwrap();
+        let result_delete = service.delete_db("Vehicle.Speed", Some(2), "");
+        let result_recreate = service.write_db("Vehicle.Speed", "40", None, "");
+
+        assert_eq!(result_create, Ok(1));
+        assert_eq!(result_update, Ok(2));
+        assert!(matches!(result_stale_write, Err(ServiceError::Aborted(_))));
+        assert!(matches!(result_stale_delete, Err(ServiceError::Aborted(_))));
+        assert_eq!((record.value, record.version), ("20".into(), 2));
+        assert!(record.modified_ms > 0);
+        assert_eq!(result_delete, Ok(()));
+        assert_eq!(result_recreate, Ok(3));
+    }
+
+    #[test]
+    fn versions_after_removal_test() {
+        let mut service = DbService::new(Box::new(MemoryFacade::new()), &Config::default());
+        let key = "Vehicle.Speed";
+
+        let version_create = service.write_db(key, "10", None, "Radio").unwrap();
+        service.delete_db(key, None, "Radio").unwrap();
+        let version_recreate = service.write_db(key, "20", None, "Radio").unwrap();
+        service.drop_namespace("Radio").unwrap();
+        let version_after_drop = service.write_db(key, "30", None, "Radio").unwrap();
+        service
+            .write_with_ttl(key, Value::from("40"), None, Some(1), "Radio")
+            .unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        let version_after_expiry = service.write_db(key, "50", None, "Radio").unwrap();
+        service
+            .write_batch(
+                &[KeyUpdate::Delete {
+                    key: key.to_string(),
+                }],
+                "Radio",
+            )
+            .unwrap();
+        let version_after_batch = service.write_db(key, "60", None, "Radio").unwrap();
+        let version_other_namespace = service.write_db(key, "70", None, "").unwrap();
+
+        assert_eq!(version_create, 1);
+        assert!(version_recreate > version_create);
+        assert!(version_after_drop > version_recreate);
+        assert!(version_after_expiry > version_after_drop + 1);
+        assert!(version_after_batch > version_after_expiry);
+        assert_eq!(version_other_namespace, 1);
+    }
+
+    #[test]
+    fn export_test() {
+        let mut service = DbService::new(Box::new(MemoryFacade::new()), &Config::default());
+        for (key, value) in [
+            ("Vehicle.Infotainment", "yes"),
+            ("Vehicle.Infotainment.Radio.CurrentStation", "wdr 4"),
+            ("Vehicle.Infotainment.Radio.Volume", "10"),
+            ("Vehicle.Infotainment.HVAC.OutdoorTemperature", "18"),
+            ("Vehicle.Communication.Radio.Volume", "7"),
+            ("test", "1"),
+        ] {
+            service.write_db(key, value, None, "").unwrap();
         }
+        service
+            .write_value("Private.Info", Value::Int(3), None, "AppName")
+            .unwrap();
+
+        let result_namespace = service.export("", "");
+        let result_subtree = service.export("Vehicle.Communication", "");
+        let result_leaf = service.export("Private.Info", "AppName");
+        let result_partial_name = service.export("Vehic", "");
+        let result_empty = service.export("", "Empty");
+
+        assert_eq!(
+            result_namespace.unwrap(),
+            serde_json::json!({
+                "Vehicle": {
+                    "Infotainment": {
+                        "$value": "yes",
+                        "Radio": { "CurrentStation": "wdr 4", "Volume": "10" },
+                        "HVAC": { "OutdoorTemperature": "18" }
+                    },
+                    "Communication": { "Radio": { "Volume": "7" } }
+                },
+                "test": "1"
+            })
+        );
+        assert_eq!(
+            result_subtree.unwrap(),
+            serde_json::json!({ "Vehicle": { "Communication": { "Radio": { "Volume": "7" } } } })
+        );
+        assert_eq!(
+            result_leaf.unwrap(),
+            serde_json::json!({ "Private": { "Info": 3 } })
+        );
+        assert!(matches!(
+            result_partial_name,
+            Err(ServiceError::NotFound(_))
+        ));
+        assert_eq!(result_empty.unwrap(), serde_json::json!({}));
+    }
+
+    #[test]
+    fn export_import_round_trip_test() {
+        let mut service = DbService::new(Box::new(MemoryFacade::new()), &Config::default());
+        let entries = [
+            ("Vehicle.Infotainment", Value::from("yes")),
+            ("Vehicle.Infotainment.Radio.Volume", Value::Int(-3)),
+            ("Vehicle.Infotainment.Radio.Balance", Value::Double(0.25)),
+            ("Vehicle.Infotainment.Radio.Gain", Value::Double(2.0)),
+            ("Vehicle.Infotainment.Radio.Muted", Value::Bool(true)),
+            (
+                "Vehicle.Infotainment.Radio.Logo",
+                Value::Bytes(vec![0, 0xFF]),
+            ),
+            (
+                "Vehicle.Infotainment.Radio.Presets",
+                Value::StringList(vec!["1live".to_string(), String::new()]),
+            ),
+        ];
+        for (key, value) in entries.clone() {
+            service.write_value(key, value, None, "Source").unwrap();
+        }
+        service
+            .write_value("Vehicle..Speed", Value::Int(1), None, "Unrepresentable")
+            .unwrap();
+        service
+            .write_value("Vehicle.Ratio", Value::Double(f64::NAN), None, "NotANumber")
+            .unwrap();
+
+        let exported = service.export("", "Source").unwrap();
+        let document: serde_json::Value =
+            serde_json::from_str(&serde_json::to_string(&exported).unwrap()).unwrap();
+        let result_import = service.import(&document, "", ImportMode::Merge, false, "Copy");
+        let result_export_again = service.export("", "Copy");
+        let result_empty_segment = service.export("", "Unrepresentable");
+        let result_nan = service.export("Vehicle", "NotANumber");
+
+        assert_eq!(result_import.unwrap().created.len(), entries.len());
+        assert_eq!(result_export_again.unwrap(), exported);
+        for (key, value) in entries {
+            assert_eq!(service.read_typed(key, None, "Copy").unwrap().value, value);
+        }
+        assert!(matches!(
+            result_empty_segment,
+            Err(ServiceError::FailedPrecondition(_))
+        ));
+        assert!(matches!(
+            result_nan,
+            Err(ServiceError::FailedPrecondition(_))
+        ));
+    }
+
+    #[test]
+    fn import_test() {
+        let mut service = DbService::new(Box::new(MemoryFacade::new()), &Config::default());
+        service
+            .write_db("Vehicle.Infotainment.Radio.Volume", "10", None, "")
+            .unwrap();
+        service
+            .write_db(
+                "Vehicle.Infotainment.Radio.CurrentStation",
+                "wdr 4",
+                None,
+                "",
+            )
+            .unwrap();
+        service.write_db("Vehicle.Speed", "50", None, "").unwrap();
+        let document = serde_json::json!({
+            "Vehicle": {
+                "Infotainment": {
+                    "$value": "yes",
+                    "Radio": { "Volume": 12, "CurrentStation": "wdr 4" }
+                }
+            }
+        });
+        let mut import = |mode: ImportMode, dry_run: bool| {
+            service.import(&document, "Vehicle.Infotainment", mode, dry_run, "")
+        };
+
+        let result_dry_run = import(ImportMode::ReplaceSubtree, true);
+        let result_fill = import(ImportMode::FillMissing, false);
+        let result_merge = import(ImportMode::Merge, false);
+        let result_outside =
+            service.import(&document, "Vehicle.Speed", ImportMode::Merge, false, "");
+        let result_invalid =
+            service.import(&serde_json::json!(1), "", ImportMode::Merge, false, "");
+
+        assert_eq!(
+            result_dry_run.unwrap(),
+            ImportReport {
+                created: vec!["Vehicle.Infotainment".to_string()],
+                updated: vec!["Vehicle.Infotainment.Radio.Volume".to_string()],
+                deleted: vec![],
+                unchanged: vec!["Vehicle.Infotainment.Radio.CurrentStation".to_string()],
+            }
+        );
+        assert_eq!(
+            result_fill.unwrap(),
+            ImportReport {
+                created: vec!["Vehicle.Infotainment".to_string()],
+                unchanged: vec![
+                    "Vehicle.Infotainment.Radio.CurrentStation".to_string(),
+                    "Vehicle.Infotainment.Radio.Volume".to_string(),
+                ],
+                ..ImportReport::default()
+            }
+        );
+        assert_eq!(
+            result_merge.unwrap().updated,
+            vec!["Vehicle.Infotainment.Radio.Volume"]
+        );
+        assert_eq!(
+            service
+                .read_typed("Vehicle.Infotainment.Radio.Volume", None, "")
+                .unwrap()
+                .value,
+            Value::Int(12)
+        );
+        assert!(matches!(
+            result_outside,
+            Err(ServiceError::InvalidArgument(_))
+        ));
+        assert!(matches!(
+            result_invalid,
+            Err(ServiceError::InvalidArgument(_))
+        ));
+
+        let result_replace = service.import(
+            &serde_json::json!({ "Vehicle.Infotainment.Radio.Volume": 12 }),
+            "Vehicle.Infotainment",
+            ImportMode::ReplaceSubtree,
+            false,
+            "",
+        );
+
+        assert_eq!(
+            result_replace.unwrap(),
+            ImportReport {
+                deleted: vec![
+                    "Vehicle.Infotainment".to_string(),
+                    "Vehicle.Infotainment.Radio.CurrentStation".to_string(),
+                ],
+                unchanged: vec!["Vehicle.Infotainment.Radio.Volume".to_string()],
+                ..ImportReport::default()
+            }
+        );
+        assert_eq!(
+            service.search_db("", "").unwrap(),
+            vec!["Vehicle.Infotainment.Radio.Volume", "Vehicle.Speed"]
+        );
+    }
+
+    #[test]
+    fn seed_defaults_test() {
+        let config = Config {
+            namespaces: BTreeMap::from([(
+                String::from("vehicle-config"),
+                NamespacePolicy {
+                    read_only: true,
+                    ..NamespacePolicy::default()
+                },
+            )]),
+            ..Config::default()
+        };
+        let mut service = DbService::new(Box::new(MemoryFacade::new()), &config);
+        service
+            .write_db(
+                "Vehicle.Cabin.HVAC.Station.Row1.Left.FanSpeed",
+                "60",
+                None,
+                "",
+            )
+            .unwrap();
+        let defaults = BTreeMap::from([
+            (
+                "Vehicle.Cabin.HVAC.Station.Row1.Left.FanSpeed".to_string(),
+                Value::Int(40),
+            ),
+            ("Vehicle.Cabin.Seat.Row1.Heating".to_string(), Value::Int(0)),
+        ]);
+
+        let result_seed = service.seed_defaults(&defaults, "");
+        service
+            .delete_db("Vehicle.Cabin.Seat.Row1.Heating", None, "")
+            .unwrap();
+        let result_seed_again = service.seed_defaults(&defaults, "");
+        let result_deleted = service.check_if_key_exists("Vehicle.Cabin.Seat.Row1.Heating", "");
+        let mut extended = defaults.clone();
+        extended.insert("Vehicle.Cabin.Sunroof.Position".to_string(), Value::Int(0));
+        let result_seed_extended = service.seed_defaults(&extended, "");
+        service.drop_namespace("").unwrap();
+        let result_seed_dropped = service.seed_defaults(&defaults, "");
+        let result_read_only = service.seed_defaults(&defaults, "vehicle-config");
+
+        assert_eq!(
+            result_seed.unwrap(),
+            Some(vec!["Vehicle.Cabin.Seat.Row1.Heating".to_string()])
+        );
+        assert_eq!(result_seed_again.unwrap(), Some(Vec::new()));
+        assert_eq!(result_deleted, Ok(false));
+        assert_eq!(
+            result_seed_extended.unwrap(),
+            Some(vec!["Vehicle.Cabin.Sunroof.Position".to_string()])
+        );
+        assert_eq!(result_seed_dropped.unwrap().unwrap().len(), 2);
+        assert_eq!(result_read_only.unwrap(), None);
+
+        let mut service = DbService::new(
+            Box::new(MemoryFacade::new()),
+            &Config {
+                defaults: DefaultsConfig {
+                    write_read_only: true,
+                    ..DefaultsConfig::default()
+                },
+                ..config
+            },
+        );
+        service
+            .write_db(
+                "Vehicle.Cabin.HVAC.Station.Row1.Left.FanSpeed",
+                "60",
+                None,
+                "",
+            )
+            .unwrap();
+        service.seed_defaults(&defaults, "").unwrap();
+        service.destroy_db().unwrap();
+
+        let result_read_only = service.seed_defaults(&defaults, "vehicle-config");
+        let result_seed_destroyed = service.seed_defaults(&defaults, "");
+
+        assert_eq!(result_read_only.unwrap().unwrap().len(), 2);
+        assert_eq!(result_seed_destroyed.unwrap().unwrap().len(), 2);
+        assert_eq!(
+            service
+                .read_db("Vehicle.Cabin.Seat.Row1.Heating", "vehicle-config")
+                .unwrap(),
+            "0"
+        );
+    }
+
+    #[test]
+    fn expiry_scan_test() {
+        let mut service = DbService::new(Box::new(MemoryFacade::new()), &Config::default());
+        service.write_db("Vehicle.Speed", "10", None, "").unwrap();
+        for key in ["Route", "Eta", "Destination"] {
+            service
+                .write_with_ttl(key, Value::from("home"), None, Some(1), "Navigation")
+                .unwrap();
+        }
+        std::thread::sleep(std::time::Duration::from_millis(10));
+
+        let mut scans = service.expiry_scans().unwrap();
+        let mut scan = scans.pop().unwrap();
+        let result_first_chunk = service.remove_expired_keys(&mut scan, 2);
+        service
+            .write_with_ttl(
+                "Route",
+                Value::from("work"),
+                None,
+                Some(3_600_000),
+                "Navigation",
+            )
+            .unwrap();
+        let result_second_chunk = service.remove_expired_keys(&mut scan, 2);
+        service.finish_expiry_scan(scan).unwrap();
+        let result_still_marked = service.may_expire("Navigation").unwrap();
+        service.delete_db("Route", None, "Navigation").unwrap();
+        let result_removed = service.remove_expired();
+        let result_unmarked = service.may_expire("Navigation").unwrap();
+
+        assert!(scans.is_empty());
+        assert!(!service.may_expire("").unwrap());
+        assert_eq!(result_first_chunk, Ok(2));
+        assert_eq!(result_second_chunk, Ok(0));
+        assert!(result_still_marked);
+        assert_eq!(result_removed, Ok(0));
+        assert!(!result_unmarked);
+        assert_eq!(service.expiry_scans().unwrap().len(), 0);
+    }
+
+    #[test]
+    fn expiry_test() {
+        let mut service = DbService::new(Box::new(MemoryFacade::new()), &Config::default());
+        service
+            .write_with_ttl("Vehicle.Route", Value::from("home"), None, Some(1), "")
+            .unwrap();
+        service
+            .write_with_ttl("Vehicle.Route.Eta", Value::Int(5), None, Some(1), "")
+            .unwrap();
+        service
+            .write_with_ttl(
+                "Vehicle.Speed",
+                Value::from("10"),
+                None,
+                Some(3_600_000),
+                "",
+            )
+            .unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        let mut events = service.subscribe();
+
+        let result_read = service.read_db("Vehicle.Route", "");
+        let result_exists = service.check_if_key_exists("Vehicle.Route.Eta", "");
+        let result_search = service.search_db("Vehicle", "");
+        let result_nodes = service.nodes_starting_in("Vehicle", Some(0), "");
+        let result_delete = service.delete_db("Vehicle.Route.Eta", None, "");
+        let result_recreate = service.write_db("Vehicle.Route", "work", Some(0), "");
+        let result_removed = service.remove_expired();
+        let result_removed_again = service.remove_expired();
+
+        assert!(matches!(result_read, Err(ServiceError::NotFound(_))));
+        assert_eq!(result_exists, Ok(false));
+        assert_eq!(result_search.unwrap(), vec!["Vehicle.Speed"]);
+        assert_eq!(result_nodes.unwrap(), vec!["Vehicle.Speed"]);
+        assert!(matches!(result_delete, Err(ServiceError::NotFound(_))));
+        assert_eq!(result_recreate, Ok(4));
+        assert_eq!(result_removed, Ok(1));
+        assert_eq!(result_removed_again, Ok(0));
+        assert_eq!(
+            events.try_recv().unwrap(),
+            ChangeEvent {
+                namespace: String::new(),
+                key: "Vehicle.Route".to_string(),
+                old_value: None,
+                new_value: Some(Value::from("work")),
+            }
+        );
+        assert_eq!(
+            events.try_recv().unwrap(),
+            ChangeEvent {
+                namespace: String::new(),
+                key: "Vehicle.Route.Eta".to_string(),
+                old_value: Some(Value::Int(5)),
+                new_value: None,
+            }
+        );
+        assert!(events.try_recv().is_err());
+        assert!(service
+            .read_record("Vehicle.Speed", "")
+            .unwrap()
+            .expires_ms
+            .is_some());
+    }
+
+    #[test]
+    fn typed_values_test() {
+        let mut service = DbService::new(Box::new(MemoryFacade::new()), &Config::default());
+        let presets = Value::StringList(vec!["1live".to_string(), "WDR 2".to_string()]);
+        service
+            .write_value("Vehicle.Cabin.Seat.Row1.Heating", Value::Int(2), None, "")
+            .unwrap();
+        service
+            .write_value("Radio.Presets", presets.clone(), None, "")
+            .unwrap();
+
+        let result_typed =
+            service.read_typed("Vehicle.Cabin.Seat.Row1.Heating", Some(ValueType::Int), "");
+        let result_any = service.read_typed("Radio.Presets", None, "");
+        let result_mismatch =
+            service.read_typed("Vehicle.Cabin.Seat.Row1.Heating", Some(ValueType::Bool), "");
+        let result_text = service.read_db("Vehicle.Cabin.Seat.Row1.Heating", "");
+        let result_list_text = service.read_db("Radio.Presets", "");
+
+        assert_eq!(result_typed.unwrap().value, Value::Int(2));
+        assert_eq!(result_any.unwrap().value, presets);
+        assert!(matches!(
+            result_mismatch,
+            Err(ServiceError::FailedPrecondition(_))
+        ));
+        assert_eq!(result_text, Ok("2".to_string()));
+        assert!(matches!(
+            result_list_text,
+            Err(ServiceError::FailedPrecondition(_))
+        ));
+    }
+
+    #[test]
+    fn compare_and_swap_test() {
+        let mut service = DbService::new(Box::new(MemoryFacade::new()), &Config::default());
+        let key = "Vehicle.Cabin.HVAC.Station.Row1.Left.Temperature";
+
+        let result_create = service.compare_and_swap(key, None, "21", "");
+        let result_create_again = service.compare_and_swap(key, None, "22", "");
+        let result_swap = service.compare_and_swap(key, Some("21"), "23", "");
+        let result_stale = service.compare_and_swap(key, Some("21"), "24", "");
+        let result_missing = service.compare_and_swap("Vehicle.Speed", Some("0"), "1", "");
+
+        assert_eq!(result_create, Ok(SwapOutcome::Swapped));
+        assert_eq!(
+            result_create_again,
+            Ok(SwapOutcome::Conflict {
+                current_value: Some("21".to_string())
+            })
+        );
+        assert_eq!(result_swap, Ok(SwapOutcome::Swapped));
+        assert_eq!(
+            result_stale,
+            Ok(SwapOutcome::Conflict {
+                current_value: Some("23".to_string())
+            })
+        );
+        assert_eq!(
+            result_missing,
+            Ok(SwapOutcome::Conflict {
+                current_value: None
+            })
+        );
+        assert_eq!(service.read_db(key, ""), Ok("23".to_string()));
+    }
+
+    #[test]
+    fn compare_and_swap_typed_value_test() {
+        let mut service = DbService::new(Box::new(MemoryFacade::new()), &Config::default());
+        service
+            .write_value("Vehicle.Speed", Value::Int(5), None, "")
+            .unwrap();
+        service
+            .write_value("Vehicle.Picture", Value::Bytes(vec![0, 1]), None, "")
+            .unwrap();
+
+        let result_int = service.compare_and_swap("Vehicle.Speed", Some("5"), "6", "");
+        let result_bytes = service.compare_and_swap("Vehicle.Picture", Some("x"), "y", "");
+
+        assert!(matches!(
+            result_int,
+            Err(ServiceError::FailedPrecondition(_))
+        ));
+        assert!(matches!(
+            result_bytes,
+            Err(ServiceError::FailedPrecondition(_))
+        ));
+        assert_eq!(
+            service.read_record("Vehicle.Speed", "").unwrap().value,
+            Value::Int(5)
+        );
+    }
+
+    #[test]
+    fn write_batch_test() {
+        let mut service = DbService::new(Box::new(MemoryFacade::new()), &Config::default());
+        service.write_db("Vehicle.Speed", "10", None, "").unwrap();
+        service
+            .write_db("Vehicle.Cabin.Light", "on", None, "")
+            .unwrap();
+        let put = |key: &str, value: &str| KeyUpdate::Put {
+            key: key.to_string(),
+            value: value.into(),
+        };
+        let delete = |key: &str| KeyUpdate::Delete {
+            key: key.to_string(),
+        };
+        let mut events = service.subscribe();
+
+        let result_empty_key = service.write_batch(&[put("Vehicle.Speed", "0"), put("", "1")], "");
+        service
+            .write_batch(
+                &[
+                    put("Vehicle.Speed", "20"),
+                    delete("Vehicle.Cabin.Light"),
+                    delete("Vehicle.Missing"),
+                    put("Vehicle.Speed", "30"),
+                ],
+                "",
+            )
+            .unwrap();
+
+        assert!(matches!(
+            result_empty_key,
+            Err(ServiceError::InvalidArgument(_))
+        ));
+        assert_eq!(service.read_db("Vehicle.Speed", "").unwrap(), "30");
+        assert_eq!(
+            service.check_if_key_exists("Vehicle.Cabin.Light", ""),
+            Ok(false)
+        );
+        let changes: Vec<_> = std::iter::from_fn(|| events.try_recv().ok())
+            .map(|event| {
+                (
+                    event.key,
+                    event.old_value.as_ref().and_then(Value::as_text),
+                    event.new_value.as_ref().and_then(Value::as_text),
+                )
+            })
+            .collect();
+        assert_eq!(
+            changes,
+            vec![
+                (
+                    "Vehicle.Speed".to_string(),
+                    Some("10".to_string()),
+                    Some("20".to_string())
+                ),
+                (
+                    "Vehicle.Cabin.Light".to_string(),
+                    Some("on".to_string()),
+                    None
+                ),
+                (
+                    "Vehicle.Speed".to_string(),
+                    Some("20".to_string()),
+                    Some("30".to_string())
+                ),
+            ]
+        );
     }
 }
@@ -0,0 +1,1601 @@
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DestroyDbRequest {}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DestroyDbResponse {}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct WriteRequest {
+    #[prost(string, tag = "1")]
+    pub key: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub value: ::prost::alloc::string::String,
+    #[prost(string, tag = "3")]
+    pub namespace: ::prost::alloc::string::String,
+    /// If set, the write fails with ABORTED unless the key currently has this
+    /// version. A key that does not exist has version 0.
+    #[prost(uint64, optional, tag = "4")]
+    pub expected_version: ::core::option::Option<u64>,
+    /// If set, the key expires this many milliseconds after the write and is
+    /// treated as deleted from then on. Must not be 0.
+    #[prost(uint64, optional, tag = "5")]
+    pub ttl_ms: ::core::option::Option<u64>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct WriteResponse {
+    /// Version of the key after the write.
+    #[prost(uint64, tag = "1")]
+    pub version: u64,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ReadRequest {
+    #[prost(string, tag = "1")]
+    pub key: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub namespace: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ReadResponse {
+    #[prost(string, tag = "1")]
+    pub value: ::prost::alloc::string::String,
+    /// Version of the key, which grows with every write and is never reused
+    /// within the namespace; 0 if the value was written before versions were
+    /// introduced.
+    #[prost(uint64, tag = "2")]
+    pub version: u64,
+    /// Time of the last write in milliseconds since the Unix epoch; 0 if unknown.
+    #[prost(uint64, tag = "3")]
+    pub modified_ms: u64,
+    /// Time in milliseconds since the Unix epoch at which the key expires;
+    /// unset if it does not expire.
+    #[prost(uint64, optional, tag = "4")]
+    pub expires_ms: ::core::option::Option<u64>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct StringList {
+    #[prost(string, repeated, tag = "1")]
+    pub values: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+/// A value that keeps the type it was written with.
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TypedValue {
+    #[prost(oneof = "typed_value::Value", tags = "1, 2, 3, 4, 5, 6")]
+    pub value: ::core::option::Option<typed_value::Value>,
+}
+/// Nested message and enum types in `TypedValue`.
+pub mod typed_value {
+    #[allow(clippy::derive_partial_eq_without_eq)]
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Value {
+        #[prost(string, tag = "1")]
+        StringValue(::prost::alloc::string::String),
+        #[prost(int64, tag = "2")]
+        IntValue(i64),
+        #[prost(double, tag = "3")]
+        DoubleValue(f64),
+        #[prost(bool, tag = "4")]
+        BoolValue(bool),
+        #[prost(bytes, tag = "5")]
+        BytesValue(::prost::alloc::vec::Vec<u8>),
+        #[prost(message, tag = "6")]
+        StringListValue(super::StringList),
+    }
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct WriteValueRequest {
+    #[prost(string, tag = "1")]
+    pub key: ::prost::alloc::string::String,
+    #[prost(message, optional, tag = "2")]
+    pub value: ::core::option::Option<TypedValue>,
+    #[prost(string, tag = "3")]
+    pub namespace: ::prost::alloc::string::String,
+    /// Like in WriteRequest.
+    #[prost(uint64, optional, tag = "4")]
+    pub expected_version: ::core::option::Option<u64>,
+    #[prost(uint64, optional, tag = "5")]
+    pub ttl_ms: ::core::option::Option<u64>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ReadValueRequest {
+    #[prost(string, tag = "1")]
+    pub key: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub namespace: ::prost::alloc::string::String,
+    /// If not ANY, the read fails with FAILED_PRECONDITION unless the value has this type.
+    #[prost(enumeration = "ValueType", tag = "3")]
+    pub r#type: i32,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ReadValueResponse {
+    #[prost(message, optional, tag = "1")]
+    pub value: ::core::option::Option<TypedValue>,
+    /// Like in ReadResponse.
+    #[prost(uint64, tag = "2")]
+    pub version: u64,
+    #[prost(uint64, tag = "3")]
+    pub modified_ms: u64,
+    #[prost(uint64, optional, tag = "4")]
+    pub expires_ms: ::core::option::Option<u64>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DeleteRequest {
+    #[prost(string, tag = "1")]
+    pub key: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub namespace: ::prost::alloc::string::String,
+    /// If set, the deletion fails with ABORTED unless the key currently has this version.
+    #[prost(uint64, optional, tag = "3")]
+    pub expected_version: ::core::option::Option<u64>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DeleteResponse {}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CompareAndSwapRequest {
+    #[prost(string, tag = "1")]
+    pub key: ::prost::alloc::string::String,
+    /// Value the key must currently have; unset if the key must not exist.
+    #[prost(string, optional, tag = "2")]
+    pub expected_value: ::core::option::Option<::prost::alloc::string::String>,
+    /// Value to write.
+    #[prost(string, tag = "3")]
+    pub value: ::prost::alloc::string::String,
+    #[prost(string, tag = "4")]
+    pub namespace: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CompareAndSwapResponse {
+    /// Whether `value` was written. If not, the key did not have the expected value.
+    #[prost(bool, tag = "1")]
+    pub swapped: bool,
+    /// Value of the key after the request; unset if the key does not exist.
+    #[prost(string, optional, tag = "2")]
+    pub current_value: ::core::option::Option<::prost::alloc::string::String>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct BatchOperation {
+    #[prost(enumeration = "batch_operation::OperationType", tag = "1")]
+    pub r#type: i32,
+    #[prost(string, tag = "2")]
+    pub key: ::prost::alloc::string::String,
+    /// Value to write; ignored for DELETE. Deleting a missing key is not an error.
+    #[prost(string, tag = "3")]
+    pub value: ::prost::alloc::string::String,
+}
+/// Nested message and enum types in `BatchOperation`.
+pub mod batch_operation {
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+    #[repr(i32)]
+    pub enum OperationType {
+        Put = 0,
+        Delete = 1,
+    }
+    impl OperationType {
+        /// String value of the enum field names used in the ProtoBuf definition.
+        ///
+        /// The values are not transformed in any way and thus are considered stable
+        /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+        pub fn as_str_name(&self) -> &'static str {
+            match self {
+                OperationType::Put => "PUT",
+                OperationType::Delete => "DELETE",
+            }
+        }
+        /// Creates an enum from field names used in the ProtoBuf definition.
+        pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+            match value {
+                "PUT" => Some(Self::Put),
+                "DELETE" => Some(Self::Delete),
+                _ => None,
+            }
+        }
+    }
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct WriteBatchRequest {
+    /// Operations in the order they are applied.
+    #[prost(message, repeated, tag = "1")]
+    pub operations: ::prost::alloc::vec::Vec<BatchOperation>,
+    #[prost(string, tag = "2")]
+    pub namespace: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct WriteBatchResponse {}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SearchRequest {
+    /// Substring the returned keys contain.
+    #[prost(string, tag = "1")]
+    pub substring: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub namespace: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SearchResponse {
+    #[prost(string, repeated, tag = "1")]
+    pub keys: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DeleteNodesRequest {
+    #[prost(string, tag = "1")]
+    pub node: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub namespace: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DeleteNodesResponse {
+    /// Keys that were deleted, without namespace.
+    #[prost(string, repeated, tag = "1")]
+    pub deleted_keys: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListNodesRequest {
+    #[prost(string, tag = "1")]
+    pub node: ::prost::alloc::string::String,
+    /// Depth of the listed nodes below `node`; 0 lists all keys below it. Defaults to 1.
+    #[prost(int32, optional, tag = "2")]
+    pub layers: ::core::option::Option<i32>,
+    #[prost(string, tag = "3")]
+    pub namespace: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListNodesResponse {
+    #[prost(string, repeated, tag = "1")]
+    pub nodes: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Namespace {
+    #[prost(string, tag = "1")]
+    pub name: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "2")]
+    pub key_count: u64,
+    /// Size of the keys and values in bytes, before compression. The space used
+    /// on disk differs.
+    #[prost(uint64, tag = "3")]
+    pub size_bytes: u64,
+    /// Whether the namespace is configured as read-only.
+    #[prost(bool, tag = "4")]
+    pub read_only: bool,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListNamespacesRequest {}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListNamespacesResponse {
+    #[prost(message, repeated, tag = "1")]
+    pub namespaces: ::prost::alloc::vec::Vec<Namespace>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct NamespaceInfoRequest {
+    #[prost(string, tag = "1")]
+    pub namespace: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct NamespaceInfoResponse {
+    /// Has no keys if the namespace contains none.
+    #[prost(message, optional, tag = "1")]
+    pub namespace: ::core::option::Option<Namespace>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DropNamespaceRequest {
+    #[prost(string, tag = "1")]
+    pub namespace: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct DropNamespaceResponse {}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ExportRequest {
+    /// Root node of the exported subtree (like in DeleteNodes); the empty node
+    /// exports the entire namespace.
+    #[prost(string, tag = "1")]
+    pub node: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub namespace: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ExportResponse {
+    /// JSON object with a member for every top-level node. Keys without
+    /// children are members with their value, other nodes are objects whose
+    /// member "$value" holds their own value, if any. Bytes are objects with the
+    /// base64-encoded value in member "$bytes".
+    #[prost(string, tag = "1")]
+    pub json: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ImportRequest {
+    /// JSON document with the keys to write.
+    #[prost(string, tag = "1")]
+    pub json: ::prost::alloc::string::String,
+    /// If not empty, all keys of the document must be in the subtree with this
+    /// root node, which is the subtree replaced by REPLACE_SUBTREE. The empty node
+    /// stands for the entire namespace.
+    #[prost(string, tag = "2")]
+    pub node: ::prost::alloc::string::String,
+    #[prost(string, tag = "3")]
+    pub namespace: ::prost::alloc::string::String,
+    #[prost(enumeration = "import_request::Mode", tag = "4")]
+    pub mode: i32,
+    /// If set, nothing is changed, but the response lists the changes the import
+    /// would make.
+    #[prost(bool, tag = "5")]
+    pub dry_run: bool,
+}
+/// Nested message and enum types in `ImportRequest`.
+pub mod import_request {
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+    #[repr(i32)]
+    pub enum Mode {
+        /// Writes the keys of the document and leaves the other keys alone.
+        Merge = 0,
+        /// Writes the keys of the document and deletes the other keys of the
+        /// subtree, like DeleteNodes.
+        ReplaceSubtree = 1,
+        /// Only writes the keys of the document that do not exist.
+        FillMissing = 2,
+    }
+    impl Mode {
+        /// String value of the enum field names used in the ProtoBuf definition.
+        ///
+        /// The values are not transformed in any way and thus are considered stable
+        /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+        pub fn as_str_name(&self) -> &'static str {
+            match self {
+                Mode::Merge => "MERGE",
+                Mode::ReplaceSubtree => "REPLACE_SUBTREE",
+                Mode::FillMissing => "FILL_MISSING",
+            }
+        }
+        /// Creates an enum from field names used in the ProtoBuf definition.
+        pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+            match value {
+                "MERGE" => Some(Self::Merge),
+                "REPLACE_SUBTREE" => Some(Self::ReplaceSubtree),
+                "FILL_MISSING" => Some(Self::FillMissing),
+                _ => None,
+            }
+        }
+    }
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ImportResponse {
+    #[prost(string, repeated, tag = "1")]
+    pub created_keys: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    #[prost(string, repeated, tag = "2")]
+    pub updated_keys: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    #[prost(string, repeated, tag = "3")]
+    pub deleted_keys: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// Keys of the document that already have the given value or, with
+    /// FILL_MISSING, exist.
+    #[prost(string, repeated, tag = "4")]
+    pub unchanged_keys: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct WatchRequest {
+    /// Watched key or, if `subtree` is set, root node of the watched subtree
+    /// (like in DeleteNodes). The empty root node watches the entire namespace.
+    #[prost(string, tag = "1")]
+    pub key: ::prost::alloc::string::String,
+    #[prost(bool, tag = "2")]
+    pub subtree: bool,
+    #[prost(string, tag = "3")]
+    pub namespace: ::prost::alloc::string::String,
+}
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct WatchEvent {
+    #[prost(enumeration = "watch_event::EventType", tag = "1")]
+    pub r#type: i32,
+    #[prost(string, tag = "2")]
+    pub key: ::prost::alloc::string::String,
+    /// Value before the change as text; unset if the key did not exist or the
+    /// value has no text form (bytes and string lists).
+    #[prost(string, optional, tag = "3")]
+    pub old_value: ::core::option::Option<::prost::alloc::string::String>,
+    /// Value after the change as text; unset if the key was deleted or the
+    /// value has no text form.
+    #[prost(string, optional, tag = "4")]
+    pub new_value: ::core::option::Option<::prost::alloc::string::String>,
+    /// Value before the change with its type; unset if the key did not exist.
+    #[prost(message, optional, tag = "5")]
+    pub old_typed_value: ::core::option::Option<TypedValue>,
+    /// Value after the change with its type; unset if the key was deleted.
+    #[prost(message, optional, tag = "6")]
+    pub new_typed_value: ::core::option::Option<TypedValue>,
+}
+/// Nested message and enum types in `WatchEvent`.
+pub mod watch_event {
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+    #[repr(i32)]
+    pub enum EventType {
+        Put = 0,
+        Delete = 1,
+    }
+    impl EventType {
+        /// String value of the enum field names used in the ProtoBuf definition.
+        ///
+        /// The values are not transformed in any way and thus are considered stable
+        /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+        pub fn as_str_name(&self) -> &'static str {
+            match self {
+                EventType::Put => "PUT",
+                EventType::Delete => "DELETE",
+            }
+        }
+        /// Creates an enum from field names used in the ProtoBuf definition.
+        pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+            match value {
+                "PUT" => Some(Self::Put),
+                "DELETE" => Some(Self::Delete),
+                _ => None,
+            }
+        }
+    }
+}
+/// Type of a TypedValue.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum ValueType {
+    /// Any type; only used to read a value regardless of its type.
+    Any = 0,
+    String = 1,
+    Int = 2,
+    Double = 3,
+    Bool = 4,
+    Bytes = 5,
+    StringList = 6,
+}
+impl ValueType {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            ValueType::Any => "ANY",
+            ValueType::String => "STRING",
+            ValueType::Int => "INT",
+            ValueType::Double => "DOUBLE",
+            ValueType::Bool => "BOOL",
+            ValueType::Bytes => "BYTES",
+            ValueType::StringList => "STRING_LIST",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "ANY" => Some(Self::Any),
+            "STRING" => Some(Self::String),
+            "INT" => Some(Self::Int),
+            "DOUBLE" => Some(Self::Double),
+            "BOOL" => Some(Self::Bool),
+            "BYTES" => Some(Self::Bytes),
+            "STRING_LIST" => Some(Self::StringList),
+            _ => None,
+        }
+    }
+}
+/// Generated client implementations.
+pub mod database_client {
+    #![allow(unused_variables, dead_code, missing_docs, clippy::let_unit_value)]
+    use tonic::codegen::http::Uri;
+    use tonic::codegen::*;
+    /// Version 2 of the Database service.
+    ///
+    /// Instead of success flags, failures are reported with gRPC status codes:
+    /// NOT_FOUND if the key or node does not exist, INVALID_ARGUMENT for malformed
+    /// requests and documents, ABORTED if the key does not have the expected version,
+    /// FAILED_PRECONDITION if the value does not have the expected type,
+    /// RESOURCE_EXHAUSTED if a write would exceed a quota of the namespace,
+    /// PERMISSION_DENIED if access to the namespace is denied,
+    /// UNAUTHENTICATED for missing credentials, UNAVAILABLE if the database cannot be
+    /// opened and INTERNAL for any other database failure. The status message
+    /// describes the failure.
+    #[derive(Debug, Clone)]
+    pub struct DatabaseClient<T> {
+        inner: tonic::client::Grpc<T>,
+    }
+    impl DatabaseClient<tonic::transport::Channel> {
+        /// Attempt to create a new client by connecting to a given endpoint.
+        pub async fn connect<D>(dst: D) -> Result<Self, tonic::transport::Error>
+        where
+            D: std::convert::TryInto<tonic::transport::Endpoint>,
+            D::Error: Into<StdError>,
+        {
+            let conn = tonic::transport::Endpoint::new(dst)?.connect().await?;
+            Ok(Self::new(conn))
+        }
+    }
+    impl<T> DatabaseClient<T>
+    where
+        T: tonic::client::GrpcService<tonic::body::BoxBody>,
+        T::Error: Into<StdError>,
+        T::ResponseBody: Body<Data = Bytes> + Send + 'static,
+        <T::ResponseBody as Body>::Error: Into<StdError> + Send,
+    {
+        pub fn new(inner: T) -> Self {
+            let inner = tonic::client::Grpc::new(inner);
+            Self { inner }
+        }
+        pub fn with_origin(inner: T, origin: Uri) -> Self {
+            let inner = tonic::client::Grpc::with_origin(inner, origin);
+            Self { inner }
+        }
+        pub fn with_interceptor<F>(
+            inner: T,
+            interceptor: F,
+        ) -> DatabaseClient<InterceptedService<T, F>>
+        where
+            F: tonic::service::Interceptor,
+            T::ResponseBody: Default,
+            T: tonic::codegen::Service<
+                http::Request<tonic::body::BoxBody>,
+                Response = http::Response<
+                    <T as tonic::client::GrpcService<tonic::body::BoxBody>>::ResponseBody,
+                >,
+            >,
+            <T as tonic::codegen::Service<http::Request<tonic::body::BoxBody>>>::Error:
+                Into<StdError> + Send + Sync,
+        {
+            DatabaseClient::new(InterceptedService::new(inner, interceptor))
+        }
+        /// Compress requests with the given encoding.
+        ///
+        /// This requires the server to support it otherwise it might respond with an
+        /// error.
+        #[must_use]
+        pub fn send_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.inner = self.inner.send_compressed(encoding);
+            self
+        }
+        /// Enable decompressing responses.
+        #[must_use]
+        pub fn accept_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.inner = self.inner.accept_compressed(encoding);
+            self
+        }
+        /// Deletes the entire data base.
+        pub async fn destroy_db(
+            &mut self,
+            request: impl tonic::IntoRequest<super::DestroyDbRequest>,
+        ) -> Result<tonic::Response<super::DestroyDbResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/storage_api.v2.Database/DestroyDB");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// Writes a key-value pair to the data base.
+        pub async fn write(
+            &mut self,
+            request: impl tonic::IntoRequest<super::WriteRequest>,
+        ) -> Result<tonic::Response<super::WriteResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/storage_api.v2.Database/Write");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// Reads the value for the given key from the data base. Int, double and bool
+        /// values are returned as text; bytes and string lists can only be read with ReadValue.
+        pub async fn read(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ReadRequest>,
+        ) -> Result<tonic::Response<super::ReadResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/storage_api.v2.Database/Read");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// Writes a typed value, which keeps its type instead of being stored as text.
+        pub async fn write_value(
+            &mut self,
+            request: impl tonic::IntoRequest<super::WriteValueRequest>,
+        ) -> Result<tonic::Response<super::WriteResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/storage_api.v2.Database/WriteValue");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// Reads the value for the given key together with its type.
+        pub async fn read_value(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ReadValueRequest>,
+        ) -> Result<tonic::Response<super::ReadValueResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/storage_api.v2.Database/ReadValue");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// Deletes the entry for the given key from the data base.
+        pub async fn delete(
+            &mut self,
+            request: impl tonic::IntoRequest<super::DeleteRequest>,
+        ) -> Result<tonic::Response<super::DeleteResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/storage_api.v2.Database/Delete");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// Writes a value only if the key currently has the expected value or, if no
+        /// value is expected, does not exist. The check and the write are atomic.
+        /// Only string values are compared: if the key holds a value of another type
+        /// (see WriteValue), the request fails with FAILED_PRECONDITION.
+        pub async fn compare_and_swap(
+            &mut self,
+            request: impl tonic::IntoRequest<super::CompareAndSwapRequest>,
+        ) -> Result<tonic::Response<super::CompareAndSwapResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path =
+                http::uri::PathAndQuery::from_static("/storage_api.v2.Database/CompareAndSwap");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// Applies a list of writes and deletes in one namespace atomically: either all
+        /// of them take effect or, if the batch fails, none does.
+        pub async fn write_batch(
+            &mut self,
+            request: impl tonic::IntoRequest<super::WriteBatchRequest>,
+        ) -> Result<tonic::Response<super::WriteBatchResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/storage_api.v2.Database/WriteBatch");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// Lists any keys that contain the given string.
+        pub async fn search(
+            &mut self,
+            request: impl tonic::IntoRequest<super::SearchRequest>,
+        ) -> Result<tonic::Response<super::SearchResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/storage_api.v2.Database/Search");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// Deletes all keys in subtree of given root. Assumes that keys follow VSS-like tree structure.
+        /// The subtree is deleted atomically: if the deletion fails, no key is deleted.
+        pub async fn delete_nodes(
+            &mut self,
+            request: impl tonic::IntoRequest<super::DeleteNodesRequest>,
+        ) -> Result<tonic::Response<super::DeleteNodesResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/storage_api.v2.Database/DeleteNodes");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// Lists all nodes in subtree of given root and depth. Assumes that keys follow VSS-like tree structure.
+        pub async fn list_nodes(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ListNodesRequest>,
+        ) -> Result<tonic::Response<super::ListNodesResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/storage_api.v2.Database/ListNodes");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// Lists the namespaces that contain keys, with the number and size of their keys.
+        pub async fn list_namespaces(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ListNamespacesRequest>,
+        ) -> Result<tonic::Response<super::ListNamespacesResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path =
+                http::uri::PathAndQuery::from_static("/storage_api.v2.Database/ListNamespaces");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// Returns the number and size of the keys in a namespace.
+        pub async fn namespace_info(
+            &mut self,
+            request: impl tonic::IntoRequest<super::NamespaceInfoRequest>,
+        ) -> Result<tonic::Response<super::NamespaceInfoResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path =
+                http::uri::PathAndQuery::from_static("/storage_api.v2.Database/NamespaceInfo");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// Deletes all keys in a namespace atomically: if the deletion fails, no key is deleted.
+        pub async fn drop_namespace(
+            &mut self,
+            request: impl tonic::IntoRequest<super::DropNamespaceRequest>,
+        ) -> Result<tonic::Response<super::DropNamespaceResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path =
+                http::uri::PathAndQuery::from_static("/storage_api.v2.Database/DropNamespace");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// Returns a subtree or an entire namespace as nested JSON that mirrors the
+        /// dot-separated hierarchy of the keys. Fails with FAILED_PRECONDITION if a key
+        /// or value cannot be imported again, e.g. a key with an empty segment or NaN.
+        pub async fn export(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ExportRequest>,
+        ) -> Result<tonic::Response<super::ExportResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/storage_api.v2.Database/Export");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// Writes the keys of a JSON document in the form returned by Export, or of a
+        /// flat JSON object of keys and values, atomically: either all changes take
+        /// effect or, if the import fails, none does.
+        pub async fn import(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ImportRequest>,
+        ) -> Result<tonic::Response<super::ImportResponse>, tonic::Status> {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/storage_api.v2.Database/Import");
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// Streams the changes of a key or of all keys in a subtree, starting with the
+        /// next change. A watcher that falls too far behind is ended with RESOURCE_EXHAUSTED.
+        pub async fn watch(
+            &mut self,
+            request: impl tonic::IntoRequest<super::WatchRequest>,
+        ) -> Result<tonic::Response<tonic::codec::Streaming<super::WatchEvent>>, tonic::Status>
+        {
+            self.inner.ready().await.map_err(|e| {
+                tonic::Status::new(
+                    tonic::Code::Unknown,
+                    format!("Service was not ready: {}", e.into()),
+                )
+            })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static("/storage_api.v2.Database/Watch");
+            self.inner
+                .server_streaming(request.into_request(), path, codec)
+                .await
+        }
+    }
+}
+/// Generated server implementations.
+pub mod database_server {
+    #![allow(unused_variables, dead_code, missing_docs, clippy::let_unit_value)]
+    use tonic::codegen::*;
+    /// Generated trait containing gRPC methods that should be implemented for use with DatabaseServer.
+    #[async_trait]
+    pub trait Database: Send + Sync + 'static {
+        /// Deletes the entire data base.
+        async fn destroy_db(
+            &self,
+            request: tonic::Request<super::DestroyDbRequest>,
+        ) -> Result<tonic::Response<super::DestroyDbResponse>, tonic::Status>;
+        /// Writes a key-value pair to the data base.
+        async fn write(
+            &self,
+            request: tonic::Request<super::WriteRequest>,
+        ) -> Result<tonic::Response<super::WriteResponse>, tonic::Status>;
+        /// Reads the value for the given key from the data base. Int, double and bool
+        /// values are returned as text; bytes and string lists can only be read with ReadValue.
+        async fn read(
+            &self,
+            request: tonic::Request<super::ReadRequest>,
+        ) -> Result<tonic::Response<super::ReadResponse>, tonic::Status>;
+        /// Writes a typed value, which keeps its type instead of being stored as text.
+        async fn write_value(
+            &self,
+            request: tonic::Request<super::WriteValueRequest>,
+        ) -> Result<tonic::Response<super::WriteResponse>, tonic::Status>;
+        /// Reads the value for the given key together with its type.
+        async fn read_value(
+            &self,
+            request: tonic::Request<super::ReadValueRequest>,
+        ) -> Result<tonic::Response<super::ReadValueResponse>, tonic::Status>;
+        /// Deletes the entry for the given key from the data base.
+        async fn delete(
+            &self,
+            request: tonic::Request<super::DeleteRequest>,
+        ) -> Result<tonic::Response<super::DeleteResponse>, tonic::Status>;
+        /// Writes a value only if the key currently has the expected value or, if no
+        /// value is expected, does not exist. The check and the write are atomic.
+        /// Only string values are compared: if the key holds a value of another type
+        /// (see WriteValue), the request fails with FAILED_PRECONDITION.
+        async fn compare_and_swap(
+            &self,
+            request: tonic::Request<super::CompareAndSwapRequest>,
+        ) -> Result<tonic::Response<super::CompareAndSwapResponse>, tonic::Status>;
+        /// Applies a list of writes and deletes in one namespace atomically: either all
+        /// of them take effect or, if the batch fails, none does.
+        async fn write_batch(
+            &self,
+            request: tonic::Request<super::WriteBatchRequest>,
+        ) -> Result<tonic::Response<super::WriteBatchResponse>, tonic::Status>;
+        /// Lists any keys that contain the given string.
+        async fn search(
+            &self,
+            request: tonic::Request<super::SearchRequest>,
+        ) -> Result<tonic::Response<super::SearchResponse>, tonic::Status>;
+        /// Deletes all keys in subtree of given root. Assumes that keys follow VSS-like tree structure.
+        /// The subtree is deleted atomically: if the deletion fails, no key is deleted.
+        async fn delete_nodes(
+            &self,
+            request: tonic::Request<super::DeleteNodesRequest>,
+        ) -> Result<tonic::Response<super::DeleteNodesResponse>, tonic::Status>;
+        /// Lists all nodes in subtree of given root and depth. Assumes that keys follow VSS-like tree structure.
+        async fn list_nodes(
+            &self,
+            request: tonic::Request<super::ListNodesRequest>,
+        ) -> Result<tonic::Response<super::ListNodesResponse>, tonic::Status>;
+        /// Lists the namespaces that contain keys, with the number and size of their keys.
+        async fn list_namespaces(
+            &self,
+            request: tonic::Request<super::ListNamespacesRequest>,
+        ) -> Result<tonic::Response<super::ListNamespacesResponse>, tonic::Status>;
+        /// Returns the number and size of the keys in a namespace.
+        async fn namespace_info(
+            &self,
+            request: tonic::Request<super::NamespaceInfoRequest>,
+        ) -> Result<tonic::Response<super::NamespaceInfoResponse>, tonic::Status>;
+        /// Deletes all keys in a namespace atomically: if the deletion fails, no key is deleted.
+        async fn drop_namespace(
+            &self,
+            request: tonic::Request<super::DropNamespaceRequest>,
+        ) -> Result<tonic::Response<super::DropNamespaceResponse>, tonic::Status>;
+        /// Returns a subtree or an entire namespace as nested JSON that mirrors the
+        /// dot-separated hierarchy of the keys. Fails with FAILED_PRECONDITION if a key
+        /// or value cannot be imported again, e.g. a key with an empty segment or NaN.
+        async fn export(
+            &self,
+            request: tonic::Request<super::ExportRequest>,
+        ) -> Result<tonic::Response<super::ExportResponse>, tonic::Status>;
+        /// Writes the keys of a JSON document in the form returned by Export, or of a
+        /// flat JSON object of keys and values, atomically: either all changes take
+        /// effect or, if the import fails, none does.
+        async fn import(
+            &self,
+            request: tonic::Request<super::ImportRequest>,
+        ) -> Result<tonic::Response<super::ImportResponse>, tonic::Status>;
+        /// Server streaming response type for the Watch method.
+        type WatchStream: futures_core::Stream<Item = Result<super::WatchEvent, tonic::Status>>
+            + Send
+            + 'static;
+        /// Streams the changes of a key or of all keys in a subtree, starting with the
+        /// next change. A watcher that falls too far behind is ended with RESOURCE_EXHAUSTED.
+        async fn watch(
+            &self,
+            request: tonic::Request<super::WatchRequest>,
+        ) -> Result<tonic::Response<Self::WatchStream>, tonic::Status>;
+    }
+    /// Version 2 of the Database service.
+    ///
+    /// Instead of success flags, failures are reported with gRPC status codes:
+    /// NOT_FOUND if the key or node does not exist, INVALID_ARGUMENT for malformed
+    /// requests and documents, ABORTED if the key does not have the expected version,
+    /// FAILED_PRECONDITION if the value does not have the expected type,
+    /// RESOURCE_EXHAUSTED if a write would exceed a quota of the namespace,
+    /// PERMISSION_DENIED if access to the namespace is denied,
+    /// UNAUTHENTICATED for missing credentials, UNAVAILABLE if the database cannot be
+    /// opened and INTERNAL for any other database failure. The status message
+    /// describes the failure.
+    #[derive(Debug)]
+    pub struct DatabaseServer<T: Database> {
+        inner: _Inner<T>,
+        accept_compression_encodings: EnabledCompressionEncodings,
+        send_compression_encodings: EnabledCompressionEncodings,
+    }
+    struct _Inner<T>(Arc<T>);
+    impl<T: Database> DatabaseServer<T> {
+        pub fn new(inner: T) -> Self {
+            Self::from_arc(Arc::new(inner))
+        }
+        pub fn from_arc(inner: Arc<T>) -> Self {
+            let inner = _Inner(inner);
+            Self {
+                inner,
+                accept_compression_encodings: Default::default(),
+                send_compression_encodings: Default::default(),
+            }
+        }
+        pub fn with_interceptor<F>(inner: T, interceptor: F) -> InterceptedService<Self, F>
+        where
+            F: tonic::service::Interceptor,
+        {
+            InterceptedService::new(Self::new(inner), interceptor)
+        }
+        /// Enable decompressing requests with the given encoding.
+        #[must_use]
+        pub fn accept_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.accept_compression_encodings.enable(encoding);
+            self
+        }
+        /// Compress responses with the given encoding, if the client supports it.
+        #[must_use]
+        pub fn send_compressed(mut self, encoding: CompressionEncoding) -> Self {
+            self.send_compression_encodings.enable(encoding);
+            self
+        }
+    }
+    impl<T, B> tonic::codegen::Service<http::Request<B>> for DatabaseServer<T>
+    where
+        T: Database,
+        B: Body + Send + 'static,
+        B::Error: Into<StdError> + Send + 'static,
+    {
+        type Response = http::Response<tonic::body::BoxBody>;
+        type Error = std::convert::Infallible;
+        type Future = BoxFuture<Self::Response, Self::Error>;
+        fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+        fn call(&mut self, req: http::Request<B>) -> Self::Future {
+            let inner = self.inner.clone();
+            match req.uri().path() {
+                "/storage_api.v2.Database/DestroyDB" => {
+                    #[allow(non_camel_case_types)]
+                    struct DestroyDBSvc<T: Database>(pub Arc<T>);
+                    impl<T: Database> tonic::server::UnaryService<super::DestroyDbRequest> for DestroyDBSvc<T> {
+                        type Response = super::DestroyDbResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::DestroyDbRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).destroy_db(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = DestroyDBSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/storage_api.v2.Database/Write" => {
+                    #[allow(non_camel_case_types)]
+                    struct WriteSvc<T: Database>(pub Arc<T>);
+                    impl<T: Database> tonic::server::UnaryService<super::WriteRequest> for WriteSvc<T> {
+                        type Response = super::WriteResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::WriteRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).write(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = WriteSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/storage_api.v2.Database/Read" => {
+                    #[allow(non_camel_case_types)]
+                    struct ReadSvc<T: Database>(pub Arc<T>);
+                    impl<T: Database> tonic::server::UnaryService<super::ReadRequest> for ReadSvc<T> {
+                        type Response = super::ReadResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ReadRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).read(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = ReadSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/storage_api.v2.Database/WriteValue" => {
+                    #[allow(non_camel_case_types)]
+                    struct WriteValueSvc<T: Database>(pub Arc<T>);
+                    impl<T: Database> tonic::server::UnaryService<super::WriteValueRequest> for WriteValueSvc<T> {
+                        type Response = super::WriteResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::WriteValueRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).write_value(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = WriteValueSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/storage_api.v2.Database/ReadValue" => {
+                    #[allow(non_camel_case_types)]
+                    struct ReadValueSvc<T: Database>(pub Arc<T>);
+                    impl<T: Database> tonic::server::UnaryService<super::ReadValueRequest> for ReadValueSvc<T> {
+                        type Response = super::ReadValueResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ReadValueRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).read_value(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = ReadValueSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/storage_api.v2.Database/Delete" => {
+                    #[allow(non_camel_case_types)]
+                    struct DeleteSvc<T: Database>(pub Arc<T>);
+                    impl<T: Database> tonic::server::UnaryService<super::DeleteRequest> for DeleteSvc<T> {
+                        type Response = super::DeleteResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::DeleteRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).delete(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = DeleteSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/storage_api.v2.Database/CompareAndSwap" => {
+                    #[allow(non_camel_case_types)]
+                    struct CompareAndSwapSvc<T: Database>(pub Arc<T>);
+                    impl<T: Database> tonic::server::UnaryService<super::CompareAndSwapRequest>
+                        for CompareAndSwapSvc<T>
+                    {
+                        type Response = super::CompareAndSwapResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::CompareAndSwapRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).compare_and_swap(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = CompareAndSwapSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/storage_api.v2.Database/WriteBatch" => {
+                    #[allow(non_camel_case_types)]
+                    struct WriteBatchSvc<T: Database>(pub Arc<T>);
+                    impl<T: Database> tonic::server::UnaryService<super::WriteBatchRequest> for WriteBatchSvc<T> {
+                        type Response = super::WriteBatchResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::WriteBatchRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).write_batch(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = WriteBatchSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/storage_api.v2.Database/Search" => {
+                    #[allow(non_camel_case_types)]
+                    struct SearchSvc<T: Database>(pub Arc<T>);
+                    impl<T: Database> tonic::server::UnaryService<super::SearchRequest> for SearchSvc<T> {
+                        type Response = super::SearchResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::SearchRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).search(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = SearchSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/storage_api.v2.Database/DeleteNodes" => {
+                    #[allow(non_camel_case_types)]
+                    struct DeleteNodesSvc<T: Database>(pub Arc<T>);
+                    impl<T: Database> tonic::server::UnaryService<super::DeleteNodesRequest> for DeleteNodesSvc<T> {
+                        type Response = super::DeleteNodesResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::DeleteNodesRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).delete_nodes(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = DeleteNodesSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/storage_api.v2.Database/ListNodes" => {
+                    #[allow(non_camel_case_types)]
+                    struct ListNodesSvc<T: Database>(pub Arc<T>);
+                    impl<T: Database> tonic::server::UnaryService<super::ListNodesRequest> for ListNodesSvc<T> {
+                        type Response = super::ListNodesResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ListNodesRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).list_nodes(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = ListNodesSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/storage_api.v2.Database/ListNamespaces" => {
+                    #[allow(non_camel_case_types)]
+                    struct ListNamespacesSvc<T: Database>(pub Arc<T>);
+                    impl<T: Database> tonic::server::UnaryService<super::ListNamespacesRequest>
+                        for ListNamespacesSvc<T>
+                    {
+                        type Response = super::ListNamespacesResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ListNamespacesRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).list_namespaces(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = ListNamespacesSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/storage_api.v2.Database/NamespaceInfo" => {
+                    #[allow(non_camel_case_types)]
+                    struct NamespaceInfoSvc<T: Database>(pub Arc<T>);
+                    impl<T: Database> tonic::server::UnaryService<super::NamespaceInfoRequest> for NamespaceInfoSvc<T> {
+                        type Response = super::NamespaceInfoResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::NamespaceInfoRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).namespace_info(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = NamespaceInfoSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/storage_api.v2.Database/DropNamespace" => {
+                    #[allow(non_camel_case_types)]
+                    struct DropNamespaceSvc<T: Database>(pub Arc<T>);
+                    impl<T: Database> tonic::server::UnaryService<super::DropNamespaceRequest> for DropNamespaceSvc<T> {
+                        type Response = super::DropNamespaceResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::DropNamespaceRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).drop_namespace(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = DropNamespaceSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/storage_api.v2.Database/Export" => {
+                    #[allow(non_camel_case_types)]
+                    struct ExportSvc<T: Database>(pub Arc<T>);
+                    impl<T: Database> tonic::server::UnaryService<super::ExportRequest> for ExportSvc<T> {
+                        type Response = super::ExportResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ExportRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).export(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = ExportSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/storage_api.v2.Database/Import" => {
+                    #[allow(non_camel_case_types)]
+                    struct ImportSvc<T: Database>(pub Arc<T>);
+                    impl<T: Database> tonic::server::UnaryService<super::ImportRequest> for ImportSvc<T> {
+                        type Response = super::ImportResponse;
+                        type Future = BoxFuture<tonic::Response<Self::Response>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ImportRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).import(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = ImportSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/storage_api.v2.Database/Watch" => {
+                    #[allow(non_camel_case_types)]
+                    struct WatchSvc<T: Database>(pub Arc<T>);
+                    impl<T: Database> tonic::server::ServerStreamingService<super::WatchRequest> for WatchSvc<T> {
+                        type Response = super::WatchEvent;
+                        type ResponseStream = T::WatchStream;
+                        type Future =
+                            BoxFuture<tonic::Response<Self::ResponseStream>, tonic::Status>;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::WatchRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).watch(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = WatchSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec).apply_compression_config(
+                            accept_compression_encodings,
+                            send_compression_encodings,
+                        );
+                        let res = grpc.server_streaming(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                _ => Box::pin(async move {
+                    Ok(http::Response::builder()
+                        .status(200)
+                        .header("grpc-status", "12")
+                        .header("content-type", "application/grpc")
+                        .body(empty_body())
+                        .unwrap())
+                }),
+            }
+        }
+    }
+    impl<T: Database> Clone for DatabaseServer<T> {
+        fn clone(&self) -> Self {
+            let inner = self.inner.clone();
+            Self {
+                inner,
+                accept_compression_encodings: self.accept_compression_encodings,
+                send_compression_encodings: self.send_compression_encodings,
+            }
+        }
+    }
+    impl<T: Database> Clone for _Inner<T> {
+        fn clone(&self) -> Self {
+            Self(self.0.clone())
+        }
+    }
+    impl<T: std::fmt::Debug> std::fmt::Debug for _Inner<T> {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "{:?}", self.0)
+        }
+    }
+    impl<T: Database> tonic::server::NamedService for DatabaseServer<T> {
+        const NAME: &'static str = "storage_api.v2.Database";
+    }
+}
@@ -0,0 +1,359 @@
+use tonic::transport::Server;
+use tonic::Request;
+
+use crate::config::TlsConfig;
+
+/// Enables TLS on `server` as described by `config`.
+#[cfg(feature = "tls")]
+pub fn configure_server(
+    server: Server,
+    config: &TlsConfig,
+) -> Result<Server, Box<dyn std::error::Error>> {
+    Ok(server.tls_config(server_tls_config(config)?)?)
+}
+
+/// Fails, since TLS is not available without the `tls` feature.
+#[cfg(not(feature = "tls"))]
+pub fn configure_server(
+    _server: Server,
+    _config: &TlsConfig,
+) -> Result<Server, Box<dyn std::error::Error>> {
+    Err("TLS is configured, but the server was built without the `tls` feature".into())
+}
+
+#[cfg(feature = "tls")]
+fn server_tls_config(
+    config: &TlsConfig,
+) -> Result<tonic::transport::ServerTlsConfig, std::io::Error> {
+    use tonic::transport::{Certificate, Identity, ServerTlsConfig};
+
+    let identity = Identity::from_pem(read_pem(&config.cert)?, read_pem(&config.key)?);
+    let mut tls = ServerTlsConfig::new().identity(identity);
+    if let Some(client_ca) = &config.client_ca {
+        tls = tls.client_ca_root(Certificate::from_pem(read_pem(client_ca)?));
+    }
+    Ok(tls)
+}
+
+#[cfg(feature = "tls")]
+fn read_pem(path: &std::path::Path) -> Result<Vec<u8>, std::io::Error> {
+    std::fs::read(path).map_err(|e| {
+        std::io::Error::new(
+            e.kind(),
+            format!("Unable to read '{}': {}", path.display(), e),
+        )
+    })
+}
+
+/// Whether the client of `request` authenticated with a certificate.
+pub fn has_peer_certificate<T>(request: &Request<T>) -> bool {
+    #[cfg(feature = "tls")]
+    {
+        request.peer_certs().is_some()
+    }
+
+    #[cfg(not(feature = "tls"))]
+    {
+        let _ = request;
+        false
+    }
+}
+
+/// Returns the common name in the subject of the certificate the client of
+/// `request` authenticated with, if any.
+pub fn peer_common_name<T>(request: &Request<T>) -> Option<String> {
+    #[cfg(feature = "tls")]
+    {
+        let certs = request.peer_certs()?;
+        let (_, cert) = x509_parser::parse_x509_certificate(certs.first()?.get_ref()).ok()?;
+        let common_name = cert.subject().iter_common_name().next()?.as_str().ok()?;
+        Some(common_name.to_string())
+    }
+
+    #[cfg(not(feature = "tls"))]
+    {
+        let _ = request;
+        None
+    }
+}
+
+#[cfg(all(test, feature = "tls"))]
+mod tests {
+    use super::*;
+    use crate::access::Permission;
+    use crate::auth::TokenInterceptor;
+    use crate::config::{AccessConfig, AccessRule, AuthConfig, Config, TokenConfig};
+    use crate::memory::MemoryFacade;
+    use crate::server::DatabaseManager;
+    use crate::storage_api::database_client::DatabaseClient;
+    use crate::storage_api::database_server::DatabaseServer;
+    use crate::storage_api::{DestroyArguments, Key, KeyValue};
+    use rcgen::{
+        BasicConstraints, Certificate, CertificateParams, DistinguishedName, DnType, IsCa,
+    };
+    use std::net::SocketAddr;
+    use std::path::{Path, PathBuf};
+    use tokio::net::TcpListener;
+    use tokio::task::JoinHandle;
+    use tokio_stream::wrappers::TcpListenerStream;
+    use tonic::transport::{Channel, ClientTlsConfig};
+
+    struct TestCertificates {
+        dir: PathBuf,
+        ca: String,
+        client_cert: String,
+        client_key: String,
+    }
+
+    /// Writes a CA, a server certificate for `localhost` and a client
+    /// certificate for `client_name`, or without a common name, signed by that
+    /// CA into a temporary directory.
+    fn generate_certificates(client_name: Option<&str>) -> (TlsConfig, TestCertificates) {
+        let dir = std::env::temp_dir().join(format!("agl-storage-tls-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let mut ca_params = CertificateParams::new(Vec::new());
+        ca_params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+        ca_params
+            .distinguished_name
+            .push(DnType::CommonName, "Test CA");
+        let ca = Certificate::from_params(ca_params).unwrap();
+        let server = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+        let mut client_params = CertificateParams::new(Vec::new());
+        client_params.distinguished_name = DistinguishedName::new();
+        if let Some(client_name) = client_name {
+            client_params
+                .distinguished_name
+                .push(DnType::CommonName, client_name);
+        }
+        let client = Certificate::from_params(client_params).unwrap();
+
+        let write = |name: &str, content: &str| -> PathBuf {
+            let path = dir.join(name);
+            std::fs::write(&path, content).unwrap();
+            path
+        };
+        let config = TlsConfig {
+            cert: write(
+                "server.pem",
+                &server.serialize_pem_with_signer(&ca).unwrap(),
+            ),
+            key: write("server.key", &server.serialize_private_key_pem()),
+            client_ca: Some(write("ca.pem", &ca.serialize_pem().unwrap())),
+        };
+        let certificates = TestCertificates {
+            dir: dir.clone(),
+            ca: ca.serialize_pem().unwrap(),
+            client_cert: client.serialize_pem_with_signer(&ca).unwrap(),
+            client_key: client.serialize_private_key_pem(),
+        };
+        (config, certificates)
+    }
+
+    #[test]
+    fn missing_certificate_file_test() {
+        let config = TlsConfig {
+            cert: PathBuf::from("/nonexistent/server.pem"),
+            key: PathBuf::from("/nonexistent/server.key"),
+            client_ca: None,
+        };
+
+        let result = server_tls_config(&config);
+
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .contains(&Path::new("/nonexistent/server.pem").display().to_string()));
+    }
+
+    /// Serves a database with `config` over TLS on a free local port.
+    async fn serve(config: &Config, tls: &TlsConfig) -> (SocketAddr, JoinHandle<()>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        let database_manager = DatabaseManager::new(Box::new(MemoryFacade::new()), config);
+        let server = configure_server(Server::builder(), tls)
+            .unwrap()
+            .add_service(DatabaseServer::with_interceptor(
+                database_manager,
+                TokenInterceptor::new(&config.auth, &config.access),
+            ));
+        let server_task = tokio::spawn(async move {
+            server
+                .serve_with_incoming(TcpListenerStream::new(listener))
+                .await
+                .unwrap();
+        });
+        (address, server_task)
+    }
+
+    /// Connects to `address`, authenticating with the client certificate.
+    async fn connect(
+        address: SocketAddr,
+        certificates: &TestCertificates,
+    ) -> DatabaseClient<Channel> {
+        let client_tls = ClientTlsConfig::new()
+            .domain_name("localhost")
+            .ca_certificate(tonic::transport::Certificate::from_pem(&certificates.ca))
+            .identity(tonic::transport::Identity::from_pem(
+                &certificates.client_cert,
+                &certificates.client_key,
+            ));
+        let channel = Channel::from_shared(format!("https://{address}"))
+            .unwrap()
+            .tls_config(client_tls)
+            .unwrap()
+            .connect()
+            .await
+            .unwrap();
+        DatabaseClient::new(channel)
+    }
+
+    fn radio_rule() -> AccessRule {
+        AccessRule {
+            uid: None,
+            gid: None,
+            common_name: Some("radio".to_string()),
+            namespaces: vec!["Radio".to_string()],
+            permission: Permission::Write,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_client_certificate_identity() {
+        // Arrange
+        let (tls, certificates) = generate_certificates(Some("radio"));
+        let config = Config {
+            access: AccessConfig {
+                enabled: true,
+                rules: vec![radio_rule()],
+            },
+            ..Config::default()
+        };
+        let (address, server_task) = serve(&config, &tls).await;
+        let mut client = connect(address, &certificates).await;
+
+        let anonymous_tls = ClientTlsConfig::new()
+            .domain_name("localhost")
+            .ca_certificate(tonic::transport::Certificate::from_pem(&certificates.ca));
+        let anonymous_channel = Channel::from_shared(format!("https://{address}"))
+            .unwrap()
+            .tls_config(anonymous_tls)
+            .unwrap()
+            .connect()
+            .await;
+
+        // Act
+        let response_write = client
+            .write(KeyValue {
+                key: "Radio.Volume".to_string(),
+                value: "12".to_string(),
+                namespace: "Radio".to_string(),
+            })
+            .await;
+        let response_read_denied = client
+            .read(Key {
+                key: "Radio.Volume".to_string(),
+                namespace: "Navigation".to_string(),
+            })
+            .await;
+        let response_destroy = client.destroy_db(DestroyArguments {}).await;
+        let response_anonymous = match anonymous_channel {
+            Ok(channel) => DatabaseClient::new(channel)
+                .destroy_db(DestroyArguments {})
+                .await
+                .is_ok(),
+            Err(_) => false,
+        };
+
+        // Assert
+        assert!(response_write.unwrap().into_inner().success);
+        assert_eq!(
+            response_read_denied.unwrap_err().code(),
+            tonic::Code::PermissionDenied
+        );
+        assert_eq!(
+            response_destroy.unwrap_err().code(),
+            tonic::Code::PermissionDenied
+        );
+        assert!(!response_anonymous);
+
+        // Clean up.
+        server_task.abort();
+        std::fs::remove_dir_all(&certificates.dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_client_certificate_without_common_name() {
+        // Arrange
+        let (tls, certificates) = generate_certificates(None);
+        let config = Config {
+            access: AccessConfig {
+                enabled: true,
+                rules: vec![radio_rule()],
+            },
+            ..Config::default()
+        };
+        let (address, server_task) = serve(&config, &tls).await;
+        let mut client = connect(address, &certificates).await;
+
+        // Act
+        let response_destroy = client.destroy_db(DestroyArguments {}).await;
+
+        // Assert
+        assert_eq!(
+            response_destroy.unwrap_err().code(),
+            tonic::Code::PermissionDenied
+        );
+
+        // Clean up.
+        server_task.abort();
+        std::fs::remove_dir_all(&certificates.dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_client_certificate_with_access_rules_disabled() {
+        // Arrange
+        let (tls, certificates) = generate_certificates(Some("radio"));
+        let config = Config {
+            access: AccessConfig {
+                enabled: false,
+                rules: vec![radio_rule()],
+            },
+            auth: AuthConfig {
+                enabled: true,
+                tokens: vec![TokenConfig {
+                    name: "radio".to_string(),
+                    token: "radio-secret".to_string(),
+                    namespaces: vec!["Radio".to_string()],
+                    permission: Permission::Write,
+                    operations: Vec::new(),
+                }],
+            },
+            ..Config::default()
+        };
+        let (address, server_task) = serve(&config, &tls).await;
+        let mut client = connect(address, &certificates).await;
+        let mut request = tonic::Request::new(DestroyArguments {});
+        request
+            .metadata_mut()
+            .insert("authorization", "Bearer radio-secret".parse().unwrap());
+
+        // Act
+        let response_no_token = client.destroy_db(DestroyArguments {}).await;
+        let response_token = client.destroy_db(request).await;
+
+        // Assert
+        assert_eq!(
+            response_no_token.unwrap_err().code(),
+            tonic::Code::Unauthenticated
+        );
+        assert_eq!(
+            response_token.unwrap_err().code(),
+            tonic::Code::PermissionDenied
+        );
+
+        // Clean up.
+        server_task.abort();
+        std::fs::remove_dir_all(&certificates.dir).unwrap();
+    }
+}
@@ -0,0 +1,290 @@
+use std::collections::BTreeMap;
+
+use serde_json::{Map, Number};
+
+use crate::record::Value;
+
+/// Member holding the value of a node that also has children, e.g.
+/// `Vehicle.Infotainment` in the README's example tree.
+pub const VALUE_MEMBER: &str = "$value";
+/// Member of the object that stands for a bytes value, holding it in base64.
+pub const BYTES_MEMBER: &str = "$bytes";
+
+/// A node of the key hierarchy with its value, if it is a key.
+#[derive(Debug, Default)]
+struct Node {
+    value: Option<Value>,
+    children: BTreeMap<String, Node>,
+}
+
+/// Builds the nested JSON form of `entries`, in which every dot-separated
+/// segment of a key is a level of objects.
+///
+/// A key without children is a member with its value. A key with children is
+/// an object that holds its value in `VALUE_MEMBER`, next to the children.
+///
+/// Fails with a message for the client if an entry could not be read back by
+/// `from_tree`: a key with an empty segment, e.g. `a..b`, or a segment named
+/// like `VALUE_MEMBER` or `BYTES_MEMBER`, or a double that JSON cannot
+/// represent, such as NaN.
+pub fn to_tree(
+    entries: impl IntoIterator<Item = (String, Value)>,
+) -> Result<serde_json::Value, String> {
+    let mut root = Node::default();
+    for (key, value) in entries {
+        if let Some(segment) = key.split('.').find(|segment| {
+            segment.is_empty() || *segment == VALUE_MEMBER || *segment == BYTES_MEMBER
+        }) {
+            return Err(String::from("Key '")
+                + &key
+                + "' has the segment '"
+                + segment
+                + "', which cannot be represented in the tree");
+        }
+        if let Value::Double(double) = value {
+            if !double.is_finite() {
+                return Err(String::from("Key '")
+                    + &key
+                    + "' has the value "
+                    + &double.to_string()
+                    + ", which cannot be represented in JSON");
+            }
+        }
+        let node = key.split('.').fold(&mut root, |node, segment| {
+            node.children.entry(segment.to_string()).or_default()
+        });
+        node.value = Some(value);
+    }
+    Ok(children_to_json(root.children))
+}
+
+fn children_to_json(children: BTreeMap<String, Node>) -> serde_json::Value {
+    serde_json::Value::Object(
+        children
+            .into_iter()
+            .map(|(segment, node)| (segment, node_to_json(node)))
+            .collect(),
+    )
+}
+
+fn node_to_json(node: Node) -> serde_json::Value {
+    match (node.value, node.children.is_empty()) {
+        (Some(value), true) => value_to_json(value),
+        (value, _) => {
+            let mut tree = children_to_json(node.children);
+            if let (Some(value), serde_json::Value::Object(members)) = (value, &mut tree) {
+                members.insert(VALUE_MEMBER.to_string(), value_to_json(value));
+            }
+            tree
+        }
+    }
+}
+
+/// Reads the keys and values of a JSON object in the form built by `to_tree`.
+///
+/// Member names may contain dots themselves, so a flat object of full key
+/// names and values, or any mix of both forms, is read as well. Fails with a
+/// message for the client if the document contains a value that cannot be
+/// stored, such as `null`, or the same key twice.
+pub fn from_tree(tree: &serde_json::Value) -> Result<BTreeMap<String, Value>, String> {
+    let members = match tree {
+        serde_json::Value::Object(members) => members,
+        _ => return Err(String::from("The document must be a JSON object")),
+    };
+    let mut entries = BTreeMap::new();
+    read_members(members, "", &mut entries)?;
+    Ok(entries)
+}
+
+fn read_members(
+    members: &Map<String, serde_json::Value>,
+    node: &str,
+    entries: &mut BTreeMap<String, Value>,
+) -> Result<(), String> {
+    for (name, member) in members {
+        let key = if name == VALUE_MEMBER {
+            if node.is_empty() {
+                return Err(String::from("The top level cannot have a value"));
+            }
+            node.to_string()
+        } else if node.is_empty() {
+            name.clone()
+        } else {
+            node.to_string() + "." + name
+        };
+        match member {
+            serde_json::Value::Object(children) if !is_bytes(children) => {
+                if name == VALUE_MEMBER {
+                    return Err(String::from("The value of node '") + node + "' is an object");
+                }
+                read_members(children, &key, entries)?;
+            }
+            _ => {
+                let value = value_from_json(member)
+                    .map_err(|message| String::from("Key '") + &key + "': " + &message)?;
+                if entries.insert(key.clone(), value).is_some() {
+                    return Err(String::from("Key '") + &key + "' is given twice");
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Whether `members` is the object that stands for a bytes value.
+fn is_bytes(members: &Map<String, serde_json::Value>) -> bool {
+    members.len() == 1 && members.contains_key(BYTES_MEMBER)
+}
+
+/// Converts a JSON value written by `value_to_json` back. Numbers without
+/// fraction or exponent become ints and must fit into 64 bits, other numbers
+/// become doubles.
+pub fn value_from_json(json: &serde_json::Value) -> Result<Value, String> {
+    match json {
+        serde_json::Value::String(value) => Ok(Value::String(value.clone())),
+        serde_json::Value::Bool(value) => Ok(Value::Bool(*value)),
+        serde_json::Value::Number(number) => match (number.as_i64(), number.as_f64()) {
+            (Some(value), _) => Ok(Value::Int(value)),
+            (None, Some(value)) if !number.is_u64() => Ok(Value::Double(value)),
+            _ => Err(String::from("Number ") + &number.to_string() + " is out of range"),
+        },
+        serde_json::Value::Array(values) => values
+            .iter()
+            .map(|value| match value {
+                serde_json::Value::String(value) => Ok(value.clone()),
+                _ => Err(String::from("Lists can only hold strings")),
+            })
+            .collect::<Result<_, _>>()
+            .map(Value::StringList),
+        serde_json::Value::Object(members) => match members.get(BYTES_MEMBER) {
+            Some(serde_json::Value::String(encoded)) if is_bytes(members) => {
+                base64::decode(encoded)
+                    .map(Value::Bytes)
+                    .map_err(|e| String::from("Invalid base64 in bytes: ") + &e.to_string())
+            }
+            _ => Err(String::from("Objects can only hold bytes")),
+        },
+        serde_json::Value::Null => Err(String::from("null cannot be stored")),
+    }
+}
+
+/// Converts `value` to JSON. Ints and doubles become numbers, bytes an object
+/// with `BYTES_MEMBER`. Doubles that JSON cannot represent, such as NaN,
+/// become `null`, which `value_from_json` rejects, so `to_tree` refuses them.
+pub fn value_to_json(value: Value) -> serde_json::Value {
+    match value {
+        Value::String(value) => serde_json::Value::String(value),
+        Value::Int(value) => serde_json::Value::Number(value.into()),
+        Value::Double(value) => {
+            Number::from_f64(value).map_or(serde_json::Value::Null, serde_json::Value::Number)
+        }
+        Value::Bool(value) => serde_json::Value::Bool(value),
+        Value::Bytes(value) => serde_json::Value::Object(Map::from_iter([(
+            BYTES_MEMBER.to_string(),
+            serde_json::Value::String(base64::encode(value)),
+        )])),
+        Value::StringList(values) => {
+            serde_json::Value::Array(values.into_iter().map(serde_json::Value::String).collect())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::tree::*;
+
+    #[test]
+    fn to_tree_test() {
+        let tree = to_tree([
+            ("Vehicle.Infotainment".to_string(), Value::from("yes")),
+            (
+                "Vehicle.Infotainment.Radio.Volume".to_string(),
+                Value::Int(10),
+            ),
+            (
+                "Vehicle.Infotainment.Radio.Presets".to_string(),
+                Value::StringList(vec!["1live".to_string()]),
+            ),
+            ("Picture".to_string(), Value::Bytes(vec![0xFF, 0])),
+            ("Factor".to_string(), Value::Double(0.5)),
+        ]);
+        let result_unrepresentable = [
+            ("Vehicle..Speed", Value::Int(1)),
+            ("Vehicle.", Value::Int(1)),
+            ("Vehicle.$value", Value::Int(1)),
+            ("Vehicle.$bytes.Size", Value::Int(1)),
+            ("Vehicle.Speed", Value::Double(f64::NAN)),
+            ("Vehicle.Speed", Value::Double(f64::NEG_INFINITY)),
+        ]
+        .map(|(key, value)| to_tree([(key.to_string(), value)]));
+
+        assert_eq!(
+            tree.unwrap(),
+            serde_json::json!({
+                "Vehicle": {
+                    "Infotainment": {
+                        "$value": "yes",
+                        "Radio": {
+                            "Presets": ["1live"],
+                            "Volume": 10
+                        }
+                    }
+                },
+                "Picture": { "$bytes": "/wA=" },
+                "Factor": 0.5
+            })
+        );
+        assert_eq!(to_tree([]).unwrap(), serde_json::json!({}));
+        for result in result_unrepresentable {
+            assert!(result.is_err());
+        }
+    }
+
+    #[test]
+    fn from_tree_test() {
+        let entries = BTreeMap::from([
+            ("Vehicle.Infotainment".to_string(), Value::from("yes")),
+            (
+                "Vehicle.Infotainment.Radio.Volume".to_string(),
+                Value::Int(10),
+            ),
+            (
+                "Vehicle.Infotainment.Radio.Presets".to_string(),
+                Value::StringList(vec!["1live".to_string()]),
+            ),
+            ("Picture".to_string(), Value::Bytes(vec![0xFF, 0])),
+            ("Factor".to_string(), Value::Double(2.0)),
+            ("Enabled".to_string(), Value::Bool(false)),
+        ]);
+
+        let result_round_trip = from_tree(&to_tree(entries.clone()).unwrap());
+        let result_flat = from_tree(&serde_json::json!({
+            "Vehicle.Infotainment": "yes",
+            "Vehicle.Infotainment.Radio": { "Volume": 10 }
+        }));
+
+        assert_eq!(result_round_trip.unwrap(), entries);
+        assert_eq!(
+            result_flat.unwrap(),
+            BTreeMap::from([
+                ("Vehicle.Infotainment".to_string(), Value::from("yes")),
+                (
+                    "Vehicle.Infotainment.Radio.Volume".to_string(),
+                    Value::Int(10)
+                ),
+            ])
+        );
+        for invalid in [
+            serde_json::json!([]),
+            serde_json::json!({ "$value": 1 }),
+            serde_json::json!({ "Vehicle": { "Speed": null } }),
+            serde_json::json!({ "Vehicle": { "$value": { "Speed": 1 } } }),
+            serde_json::json!({ "Presets": [1] }),
+            serde_json::json!({ "Picture": { "$bytes": "not base64!" } }),
+            serde_json::json!({ "Vehicle.Speed": 1, "Vehicle": { "Speed": 2 } }),
+            serde_json::json!({ "Speed": 18446744073709551615u64 }),
+        ] {
+            assert!(from_tree(&invalid).is_err(), "{invalid}");
+        }
+    }
+}